@@ -1,4 +1,8 @@
+// `#[program]` expands to a call to the deprecated `AccountInfo::realloc` in anchor-lang 0.31.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, TransferChecked};
 
 declare_id!("g6u4N2d2vwrihSzUGDFYWA12sNNiHFZF7eXy9i3VX5v");
 
@@ -6,76 +10,260 @@ declare_id!("g6u4N2d2vwrihSzUGDFYWA12sNNiHFZF7eXy9i3VX5v");
 pub mod my_escrow_project {
     use super::*;
 
-    pub fn initialize_escrow(ctx: Context<InitializeEscrow>, amount: u64, item_details: String) -> Result<()> {
+    /// Lists an item for sale under a seller-chosen `escrow_id`, so a seller can run several
+    /// escrows at once. Nothing is deposited until a buyer funds the escrow.
+    pub fn initialize_escrow(
+        ctx: Context<InitializeEscrow>,
+        escrow_id: u64,
+        amount: u64,
+        item_details: String,
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        require!(item_details.len() <= Escrow::MAX_ITEM_DETAILS_LEN, EscrowError::ItemDetailsTooLong);
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.id = escrow_id;
+        escrow.amount = amount;
+        escrow.item_details = item_details;
+        escrow.state = EscrowState::Created;
+        escrow.seller = *ctx.accounts.seller.key;
+        escrow.buyer = Pubkey::default();
+        escrow.bump = ctx.bumps.escrow;
+        Ok(())
+    }
+
+    /// The buyer deposits the price, which stays in the escrow until they confirm delivery.
+    pub fn fund_escrow(ctx: Context<FundEscrow>) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
+        require!(escrow.state == EscrowState::Created, EscrowError::InvalidEscrowState);
 
         let ix = anchor_lang::system_program::Transfer {
-            from: ctx.accounts.seller.to_account_info(),
+            from: ctx.accounts.buyer.to_account_info(),
             to: escrow.to_account_info(),
         };
 
         anchor_lang::system_program::transfer(
             CpiContext::new(ctx.accounts.system_program.to_account_info(), ix),
-            amount,
+            escrow.amount,
         )?;
 
-        escrow.amount = amount;
-        escrow.item_details = item_details;
-        escrow.is_active = true;
-        escrow.seller = *ctx.accounts.seller.key;
-        escrow.buyer = Pubkey::default();
-        escrow.bump = ctx.bumps.escrow;
+        escrow.buyer = *ctx.accounts.buyer.key;
+        escrow.state = EscrowState::Funded;
         Ok(())
     }
 
-    pub fn accept_escrow(ctx: Context<AcceptEscrow>) -> Result<()> {
+    pub fn mark_delivered(ctx: Context<MarkDelivered>) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
-        require!(escrow.is_active, EscrowError::EscrowNotActive);
-        require!(escrow.buyer == Pubkey::default(), EscrowError::EscrowAlreadyAccepted);
-        
-        escrow.buyer = *ctx.accounts.buyer.key;
-
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
-        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
+        require!(escrow.state == EscrowState::Funded, EscrowError::InvalidEscrowState);
 
+        escrow.state = EscrowState::Delivered;
         Ok(())
     }
 
-    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+    /// Releases the deposit to the seller. Only the buyer can do this, and only after delivery.
+    pub fn confirm_delivery(ctx: Context<ConfirmDelivery>) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
-        require!(escrow.is_active, EscrowError::EscrowNotActive);
-        require!(escrow.buyer == Pubkey::default(), EscrowError::EscrowAlreadyAccepted);
-        
-        escrow.is_active = false;
+        require!(escrow.state == EscrowState::Delivered, EscrowError::InvalidEscrowState);
+
+        escrow.state = EscrowState::Completed;
 
         **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
         **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
         Ok(())
     }
+
+    /// Returns the deposit to the buyer. The buyer can back out until the item is marked delivered;
+    /// after that only the seller can refund. There is no timeout: a delivered escrow holds the
+    /// deposit until the buyer confirms or the seller refunds.
+    pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let authority = ctx.accounts.authority.key();
+        match escrow.state {
+            EscrowState::Funded => require!(
+                authority == escrow.seller || authority == escrow.buyer,
+                EscrowError::Unauthorized
+            ),
+            EscrowState::Delivered => require!(authority == escrow.seller, EscrowError::Unauthorized),
+            _ => return err!(EscrowError::InvalidEscrowState),
+        }
+
+        escrow.state = EscrowState::Refunded;
+
+        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
+        **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
+        Ok(())
+    }
+
+    /// Withdraws a listing nobody has funded and returns its rent to the seller.
+    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+        require!(ctx.accounts.escrow.state == EscrowState::Created, EscrowError::InvalidEscrowState);
+        Ok(())
+    }
+
+    /// Closes a settled escrow and returns its rent to the seller.
+    pub fn close_escrow(ctx: Context<CloseEscrow>) -> Result<()> {
+        require!(
+            matches!(ctx.accounts.escrow.state, EscrowState::Completed | EscrowState::Refunded),
+            EscrowError::InvalidEscrowState
+        );
+        Ok(())
+    }
+
+    /// Offers `amount_a` of mint A, held in a program-owned vault, for `amount_b` of mint B.
+    pub fn make_token_escrow(
+        ctx: Context<MakeTokenEscrow>,
+        escrow_id: u64,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<()> {
+        require!(amount_a > 0 && amount_b > 0, EscrowError::InvalidAmount);
+
+        let token_escrow = &mut ctx.accounts.token_escrow;
+        token_escrow.id = escrow_id;
+        token_escrow.maker = *ctx.accounts.maker.key;
+        token_escrow.mint_a = ctx.accounts.mint_a.key();
+        token_escrow.mint_b = ctx.accounts.mint_b.key();
+        token_escrow.amount_a = amount_a;
+        token_escrow.amount_b = amount_b;
+        token_escrow.bump = ctx.bumps.token_escrow;
+        token_escrow.vault_bump = ctx.bumps.vault;
+
+        let ix = TransferChecked {
+            from: ctx.accounts.maker_token_a.to_account_info(),
+            mint: ctx.accounts.mint_a.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.maker.to_account_info(),
+        };
+        token::transfer_checked(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), ix),
+            amount_a,
+            ctx.accounts.mint_a.decimals,
+        )
+    }
+
+    /// Swaps atomically: the taker pays the maker in mint B and receives the vault's mint A. The
+    /// vault and escrow are then closed and their rent goes back to the maker.
+    pub fn take_token_escrow(ctx: Context<TakeTokenEscrow>) -> Result<()> {
+        let token_escrow = &ctx.accounts.token_escrow;
+
+        let ix = TransferChecked {
+            from: ctx.accounts.taker_token_b.to_account_info(),
+            mint: ctx.accounts.mint_b.to_account_info(),
+            to: ctx.accounts.maker_token_b.to_account_info(),
+            authority: ctx.accounts.taker.to_account_info(),
+        };
+        token::transfer_checked(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), ix),
+            token_escrow.amount_b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+
+        let id = token_escrow.id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"token_escrow", token_escrow.maker.as_ref(), id.as_ref(), &[token_escrow.bump]]];
+        let ix = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.mint_a.to_account_info(),
+            to: ctx.accounts.taker_token_a.to_account_info(),
+            authority: token_escrow.to_account_info(),
+        };
+        token::transfer_checked(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), ix, signer_seeds),
+            ctx.accounts.vault.amount,
+            ctx.accounts.mint_a.decimals,
+        )?;
+
+        let ix = CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.maker.to_account_info(),
+            authority: token_escrow.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            ix,
+            signer_seeds,
+        ))
+    }
+
+    /// Returns everything in the vault to the maker and closes both the vault and the escrow.
+    pub fn cancel_token_escrow(ctx: Context<CancelTokenEscrow>) -> Result<()> {
+        let token_escrow = &ctx.accounts.token_escrow;
+        let id = token_escrow.id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"token_escrow", token_escrow.maker.as_ref(), id.as_ref(), &[token_escrow.bump]]];
+
+        let ix = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.mint_a.to_account_info(),
+            to: ctx.accounts.maker_token_a.to_account_info(),
+            authority: token_escrow.to_account_info(),
+        };
+        token::transfer_checked(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), ix, signer_seeds),
+            ctx.accounts.vault.amount,
+            ctx.accounts.mint_a.decimals,
+        )?;
+
+        let ix = CloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.maker.to_account_info(),
+            authority: token_escrow.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            ix,
+            signer_seeds,
+        ))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowState {
+    Created,
+    Funded,
+    Delivered,
+    Completed,
+    Refunded,
 }
 
 #[account]
 pub struct Escrow {
+    pub id: u64,             // 8
     pub seller: Pubkey,      // 32
     pub buyer: Pubkey,       // 32
     pub amount: u64,         // 8
     pub item_details: String, // 4 + len
-    pub is_active: bool,     // 1
+    pub state: EscrowState,  // 1
     pub bump: u8             // 1
 }
 
 impl Escrow {
-    pub const LEN: usize = 32 + 32 + 8 + (4 + 200) + 1 + 1; // 278 bytes (assuming max 200 chars for item_details)
+    pub const MAX_ITEM_DETAILS_LEN: usize = 200;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + (4 + Self::MAX_ITEM_DETAILS_LEN) + 1 + 1; // 286 bytes
+}
+
+#[account]
+pub struct TokenEscrow {
+    pub id: u64,             // 8
+    pub maker: Pubkey,       // 32
+    pub mint_a: Pubkey,      // 32
+    pub mint_b: Pubkey,      // 32
+    pub amount_a: u64,       // 8
+    pub amount_b: u64,       // 8
+    pub bump: u8,            // 1
+    pub vault_bump: u8       // 1
+}
+
+impl TokenEscrow {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 1; // 122 bytes
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, item_details: String)]
+#[instruction(escrow_id: u64)]
 pub struct InitializeEscrow<'info> {
     #[account(
         init,
         payer = seller,
         space = 8 + Escrow::LEN,
-        seeds = [b"escrow", seller.key().as_ref()],
+        seeds = [b"escrow", seller.key().as_ref(), escrow_id.to_le_bytes().as_ref()],
         bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -87,22 +275,26 @@ pub struct InitializeEscrow<'info> {
 }
 
 #[derive(Accounts)]
-pub struct AcceptEscrow<'info> {
+pub struct FundEscrow<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", escrow.seller.as_ref()],
-        bump = escrow.bump
+        seeds = [b"escrow", escrow.seller.as_ref(), escrow.id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.seller != buyer.key() @ EscrowError::SellerCannotBuy
     )]
     pub escrow: Account<'info, Escrow>,
 
+    #[account(mut)]
     pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>
 }
 
 #[derive(Accounts)]
-pub struct CancelEscrow<'info> {
+pub struct MarkDelivered<'info> {
     #[account(
         mut,
-        seeds = [b"escrow", seller.key().as_ref()],
+        seeds = [b"escrow", seller.key().as_ref(), escrow.id.to_le_bytes().as_ref()],
         bump = escrow.bump,
         has_one = seller
     )]
@@ -111,10 +303,208 @@ pub struct CancelEscrow<'info> {
     pub seller: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ConfirmDelivery<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", seller.key().as_ref(), escrow.id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = seller,
+        has_one = buyer
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: only receives the deposit; checked against `escrow.seller`.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.seller.as_ref(), escrow.id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = buyer
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: only receives the deposit; checked against `escrow.buyer`.
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", seller.key().as_ref(), escrow.id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = seller,
+        close = seller
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", seller.key().as_ref(), escrow.id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = seller,
+        close = seller
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(escrow_id: u64)]
+pub struct MakeTokenEscrow<'info> {
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + TokenEscrow::LEN,
+        seeds = [b"token_escrow", maker.key().as_ref(), escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"vault", token_escrow.key().as_ref()],
+        bump,
+        token::mint = mint_a,
+        token::authority = token_escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = maker_token_a.mint == mint_a.key() @ EscrowError::InvalidTokenAccount,
+        constraint = maker_token_a.owner == maker.key() @ EscrowError::InvalidTokenAccount
+    )]
+    pub maker_token_a: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct TakeTokenEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_escrow", maker.key().as_ref(), token_escrow.id.to_le_bytes().as_ref()],
+        bump = token_escrow.bump,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        close = maker
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", token_escrow.key().as_ref()],
+        bump = token_escrow.vault_bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = taker_token_a.mint == mint_a.key() @ EscrowError::InvalidTokenAccount,
+        constraint = taker_token_a.owner == taker.key() @ EscrowError::InvalidTokenAccount
+    )]
+    pub taker_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = taker_token_b.mint == mint_b.key() @ EscrowError::InvalidTokenAccount,
+        constraint = taker_token_b.owner == taker.key() @ EscrowError::InvalidTokenAccount
+    )]
+    pub taker_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = maker_token_b.mint == mint_b.key() @ EscrowError::InvalidTokenAccount,
+        constraint = maker_token_b.owner == maker.key() @ EscrowError::InvalidTokenAccount
+    )]
+    pub maker_token_b: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    pub taker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTokenEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_escrow", maker.key().as_ref(), token_escrow.id.to_le_bytes().as_ref()],
+        bump = token_escrow.bump,
+        has_one = maker,
+        has_one = mint_a,
+        close = maker
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", token_escrow.key().as_ref()],
+        bump = token_escrow.vault_bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint_a: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = maker_token_a.mint == mint_a.key() @ EscrowError::InvalidTokenAccount,
+        constraint = maker_token_a.owner == maker.key() @ EscrowError::InvalidTokenAccount
+    )]
+    pub maker_token_a: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
 #[error_code]
 pub enum EscrowError {
-    #[msg("Escrow is not active")]
-    EscrowNotActive,
-    #[msg("Escrow has already been accepted")]
-    EscrowAlreadyAccepted,
-}
\ No newline at end of file
+    #[msg("Escrow is not in a state that allows this action")]
+    InvalidEscrowState,
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("Escrow amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Item details exceed the maximum length")]
+    ItemDetailsTooLong,
+    #[msg("Seller cannot fund their own escrow")]
+    SellerCannotBuy,
+    #[msg("Token account has the wrong mint or owner")]
+    InvalidTokenAccount,
+}
@@ -0,0 +1,448 @@
+//! In-process bank for running the program without a validator.
+//!
+//! Instructions are serialized into the same input buffer the BPF loader builds and handed to
+//! `my_escrow_project::entry`. Syscalls are stubbed: the clock comes from the bank and CPIs into the system
+//! and SPL token programs run natively.
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+use anchor_lang::{
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::{self, ProgramResult, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        program_option::COption,
+        program_pack::Pack,
+        program_stubs::{self, SyscallStubs},
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    system_program, AccountDeserialize, InstructionData, ToAccountMetas,
+};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountState {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+#[derive(Default)]
+struct InvokeContext {
+    program_id: Pubkey,
+    clock: Clock,
+}
+
+thread_local! {
+    static INVOKE_CONTEXT: RefCell<InvokeContext> = RefCell::new(InvokeContext::default());
+}
+
+pub struct Bank {
+    accounts: HashMap<Pubkey, AccountState>,
+    clock: Clock,
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut bank = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+        };
+        for program_id in [my_escrow_project::ID, system_program::ID, spl_token::ID] {
+            bank.set_account(
+                program_id,
+                AccountState {
+                    lamports: 1,
+                    data: Vec::new(),
+                    owner: Pubkey::default(),
+                    executable: true,
+                },
+            );
+        }
+        bank
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    pub fn set_time(&mut self, unix_timestamp: i64) {
+        self.clock.unix_timestamp = unix_timestamp;
+    }
+
+    pub fn warp(&mut self, seconds: i64) {
+        self.clock.unix_timestamp += seconds;
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: AccountState) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&AccountState> {
+        self.accounts.get(key)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |account| account.lamports)
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts.entry(*key).or_default().lamports += lamports;
+    }
+
+    /// Creates a system-owned wallet funded with `sol` SOL.
+    pub fn wallet(&mut self, sol: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.airdrop(&key, sol * LAMPORTS_PER_SOL);
+        key
+    }
+
+    pub fn get<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self
+            .accounts
+            .get(key)
+            .unwrap_or_else(|| panic!("account {key} does not exist"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("account data does not deserialize")
+    }
+
+    pub fn create_mint(&mut self, authority: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: COption::Some(*authority),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(mint, data);
+        mint
+    }
+
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(key, data);
+
+        let mint_account = self.accounts.get_mut(mint).expect("mint does not exist");
+        let mut mint_state = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+        mint_state.supply += amount;
+        mint_state.pack_into_slice(&mut mint_account.data);
+        key
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        let account = self.accounts.get(key).expect("token account does not exist");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    fn set_token_program_account(&mut self, key: Pubkey, data: Vec<u8>) {
+        self.set_account(
+            key,
+            AccountState {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: spl_token::ID,
+                executable: false,
+            },
+        );
+    }
+
+    /// Executes an escrow instruction atomically: account changes are only committed on success.
+    ///
+    /// Every account the instruction marks as a signer must appear in `signers`.
+    pub fn process(&mut self, instruction: Instruction, signers: &[&Pubkey]) -> Result<(), ProgramError> {
+        assert_eq!(instruction.program_id, my_escrow_project::ID, "the bank only executes the escrow program");
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+            if !signers.contains(&&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+
+        let (mut input, keys) = self.serialize(&instruction);
+        INVOKE_CONTEXT.with(|context| {
+            *context.borrow_mut() = InvokeContext {
+                program_id: instruction.program_id,
+                clock: self.clock.clone(),
+            };
+        });
+
+        let post = {
+            let (program_id, account_infos, data) =
+                unsafe { entrypoint::deserialize(input.as_mut_ptr() as *mut u8) };
+            my_escrow_project::entry(program_id, &account_infos, data)?;
+            self.collect_post_state(&instruction.accounts, &keys, &account_infos)
+        };
+
+        let pre_lamports: u64 = keys.iter().map(|key| self.lamports(key)).sum();
+        let post_lamports: u64 = post.iter().map(|(_, account)| account.lamports).sum();
+        assert_eq!(pre_lamports, post_lamports, "instruction did not conserve lamports");
+
+        for (key, account) in post {
+            if account.lamports == 0 {
+                self.accounts.remove(&key);
+            } else {
+                self.accounts.insert(key, account);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds and processes an instruction from anchor's generated account and argument structs.
+    pub fn send(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        args: impl InstructionData,
+        signers: &[&Pubkey],
+    ) -> Result<(), ProgramError> {
+        let instruction = Instruction {
+            program_id: my_escrow_project::ID,
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+        self.process(instruction, signers)
+    }
+
+    fn serialize(&self, instruction: &Instruction) -> (Vec<u64>, Vec<Pubkey>) {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        buffer.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+
+        for meta in &instruction.accounts {
+            if let Some(index) = keys.iter().position(|key| *key == meta.pubkey) {
+                buffer.push(index as u8);
+                buffer.extend_from_slice(&[0; 7]);
+                continue;
+            }
+            keys.push(meta.pubkey);
+
+            // Duplicated metas share the union of their privileges, as they do in the runtime.
+            let (is_signer, is_writable) = merged_privileges(&instruction.accounts, &meta.pubkey);
+            let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_default();
+            buffer.push(NON_DUP_MARKER);
+            buffer.push(is_signer as u8);
+            buffer.push(is_writable as u8);
+            buffer.push(account.executable as u8);
+            buffer.extend_from_slice(&[0; 4]);
+            buffer.extend_from_slice(meta.pubkey.as_ref());
+            buffer.extend_from_slice(account.owner.as_ref());
+            buffer.extend_from_slice(&account.lamports.to_le_bytes());
+            buffer.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            buffer.extend_from_slice(&account.data);
+            buffer.resize(buffer.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            buffer.resize(buffer.len().next_multiple_of(8), 0);
+            buffer.extend_from_slice(&u64::MAX.to_le_bytes());
+        }
+
+        buffer.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(&instruction.data);
+        buffer.extend_from_slice(instruction.program_id.as_ref());
+
+        // Copy into a u64 buffer so the loader's aligned reads are valid.
+        let mut input = vec![0u64; buffer.len().div_ceil(8)];
+        unsafe {
+            std::ptr::copy_nonoverlapping(buffer.as_ptr(), input.as_mut_ptr() as *mut u8, buffer.len());
+        }
+        (input, keys)
+    }
+
+    fn collect_post_state(
+        &self,
+        metas: &[AccountMeta],
+        keys: &[Pubkey],
+        account_infos: &[AccountInfo],
+    ) -> Vec<(Pubkey, AccountState)> {
+        keys.iter()
+            .map(|key| {
+                let info = account_infos.iter().find(|info| info.key == key).unwrap();
+                let post = AccountState {
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                    executable: info.executable,
+                };
+                let (_, is_writable) = merged_privileges(metas, key);
+                if !is_writable {
+                    let pre = self.accounts.get(key).cloned().unwrap_or_default();
+                    assert_eq!(pre, post, "read-only account {key} was modified");
+                }
+                (*key, post)
+            })
+            .collect()
+    }
+}
+
+fn merged_privileges(metas: &[AccountMeta], key: &Pubkey) -> (bool, bool) {
+    metas
+        .iter()
+        .filter(|meta| meta.pubkey == *key)
+        .fold((false, false), |(is_signer, is_writable), meta| {
+            (is_signer || meta.is_signer, is_writable || meta.is_writable)
+        })
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = INVOKE_CONTEXT.with(|context| context.borrow().clock.clone());
+        unsafe { std::ptr::write(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = INVOKE_CONTEXT.with(|context| context.borrow().program_id);
+        let pda_signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let mut info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?
+                .clone();
+            if meta.is_signer && !info.is_signer {
+                if !pda_signers.contains(&meta.pubkey) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                info.is_signer = true;
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            infos.push(info);
+        }
+
+        if instruction.program_id == system_program::ID {
+            process_system_instruction(&infos, &instruction.data)
+        } else if instruction.program_id == spl_token::ID {
+            spl_token::processor::Processor::process(&spl_token::ID, &infos, &instruction.data)
+        } else {
+            Err(ProgramError::IncorrectProgramId)
+        }
+    }
+}
+
+fn process_system_instruction(infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let read_u64 = |offset: usize| -> Result<u64, ProgramError> {
+        data.get(offset..offset + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let read_pubkey = |offset: usize| -> Result<Pubkey, ProgramError> {
+        data.get(offset..offset + 32)
+            .map(|bytes| Pubkey::try_from(bytes).unwrap())
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let tag = data
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let account = |index: usize| infos.get(index).ok_or(ProgramError::NotEnoughAccountKeys);
+
+    match tag {
+        // CreateAccount { lamports, space, owner }
+        0 => {
+            let (from, to) = (account(0)?, account(1)?);
+            if to.lamports() != 0 || !to.data_is_empty() || *to.owner != system_program::ID {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            move_lamports(from, to, read_u64(4)?)?;
+            to.resize(read_u64(12)? as usize)?;
+            to.assign(&read_pubkey(20)?);
+            Ok(())
+        }
+        // Assign { owner }
+        1 => {
+            let target = account(0)?;
+            require_system_owned(target)?;
+            target.assign(&read_pubkey(4)?);
+            Ok(())
+        }
+        // Transfer { lamports }
+        2 => {
+            let from = account(0)?;
+            require_system_owned(from)?;
+            move_lamports(from, account(1)?, read_u64(4)?)
+        }
+        // Allocate { space }
+        8 => {
+            let target = account(0)?;
+            require_system_owned(target)?;
+            target.resize(read_u64(4)? as usize)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn require_system_owned(info: &AccountInfo) -> ProgramResult {
+    if *info.owner != system_program::ID || !info.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    let remaining = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+/// The `ProgramError` an anchor error surfaces as once it leaves the program.
+pub fn anchor_error(error: impl Into<anchor_lang::error::Error>) -> ProgramError {
+    error.into().into()
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &my_escrow_project::ID).0
+}
@@ -0,0 +1,288 @@
+mod common;
+
+use anchor_lang::{
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, pda, Bank, LAMPORTS_PER_SOL};
+use my_escrow_project::{accounts, instruction, Escrow, EscrowError, EscrowState};
+
+const ESCROW_ID: u64 = 1;
+const PRICE: u64 = LAMPORTS_PER_SOL / 2;
+
+struct Sale {
+    bank: Bank,
+    seller: Pubkey,
+    buyer: Pubkey,
+    escrow: Pubkey,
+}
+
+/// Lists an item under `escrow_id` and returns the escrow's address.
+fn list(bank: &mut Bank, seller: Pubkey, escrow_id: u64) -> Pubkey {
+    let escrow = pda(&[b"escrow", seller.as_ref(), &escrow_id.to_le_bytes()]);
+    bank.send(
+        accounts::InitializeEscrow {
+            escrow,
+            seller,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeEscrow {
+            escrow_id,
+            amount: PRICE,
+            item_details: "Fountain pen".to_string(),
+        },
+        &[&seller],
+    )
+    .unwrap();
+    escrow
+}
+
+fn fund(bank: &mut Bank, escrow: Pubkey, buyer: Pubkey) -> Result<(), ProgramError> {
+    bank.send(
+        accounts::FundEscrow {
+            escrow,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::FundEscrow {},
+        &[&buyer],
+    )
+}
+
+impl Sale {
+    /// A listing nobody has funded yet.
+    fn listed() -> Self {
+        let mut bank = Bank::new();
+        let seller = bank.wallet(1);
+        let buyer = bank.wallet(1);
+        let escrow = list(&mut bank, seller, ESCROW_ID);
+
+        Self {
+            bank,
+            seller,
+            buyer,
+            escrow,
+        }
+    }
+
+    /// A listing the buyer has already funded.
+    fn funded() -> Self {
+        let mut sale = Self::listed();
+        fund(&mut sale.bank, sale.escrow, sale.buyer).unwrap();
+        sale
+    }
+
+    /// A funded listing the seller has marked delivered.
+    fn delivered() -> Self {
+        let mut sale = Self::funded();
+        let seller = sale.seller;
+        sale.bank
+            .send(
+                accounts::MarkDelivered {
+                    escrow: sale.escrow,
+                    seller,
+                },
+                instruction::MarkDelivered {},
+                &[&seller],
+            )
+            .unwrap();
+        sale
+    }
+
+    fn state(&self) -> EscrowState {
+        self.bank.get::<Escrow>(&self.escrow).state
+    }
+
+    fn confirm(&mut self) -> Result<(), ProgramError> {
+        let buyer = self.buyer;
+        self.bank.send(
+            accounts::ConfirmDelivery {
+                escrow: self.escrow,
+                seller: self.seller,
+                buyer,
+            },
+            instruction::ConfirmDelivery {},
+            &[&buyer],
+        )
+    }
+
+    fn cancel(&mut self) -> Result<(), ProgramError> {
+        let seller = self.seller;
+        self.bank.send(
+            accounts::CancelEscrow {
+                escrow: self.escrow,
+                seller,
+            },
+            instruction::CancelEscrow {},
+            &[&seller],
+        )
+    }
+
+    fn close(&mut self) -> Result<(), ProgramError> {
+        let seller = self.seller;
+        self.bank.send(
+            accounts::CloseEscrow {
+                escrow: self.escrow,
+                seller,
+            },
+            instruction::CloseEscrow {},
+            &[&seller],
+        )
+    }
+
+    fn refund(&mut self, authority: Pubkey) -> Result<(), ProgramError> {
+        self.bank.send(
+            accounts::RefundEscrow {
+                escrow: self.escrow,
+                buyer: self.buyer,
+                authority,
+            },
+            instruction::RefundEscrow {},
+            &[&authority],
+        )
+    }
+}
+
+#[test]
+fn confirming_delivery_pays_the_seller() {
+    let mut sale = Sale::delivered();
+    let seller_before = sale.bank.lamports(&sale.seller);
+
+    sale.confirm().unwrap();
+
+    assert_eq!(sale.state(), EscrowState::Completed);
+    assert_eq!(sale.bank.lamports(&sale.seller), seller_before + PRICE);
+}
+
+#[test]
+fn the_buyer_can_back_out_before_delivery() {
+    let mut sale = Sale::funded();
+    let buyer_before = sale.bank.lamports(&sale.buyer);
+    let buyer = sale.buyer;
+
+    sale.refund(buyer).unwrap();
+
+    assert_eq!(sale.state(), EscrowState::Refunded);
+    assert_eq!(sale.bank.lamports(&sale.buyer), buyer_before + PRICE);
+}
+
+#[test]
+fn the_seller_can_refund_before_and_after_delivery() {
+    let mut sale = Sale::funded();
+    let buyer_before = sale.bank.lamports(&sale.buyer);
+    let seller = sale.seller;
+    sale.refund(seller).unwrap();
+    assert_eq!(sale.state(), EscrowState::Refunded);
+    assert_eq!(sale.bank.lamports(&sale.buyer), buyer_before + PRICE);
+
+    let mut sale = Sale::delivered();
+    let buyer_before = sale.bank.lamports(&sale.buyer);
+    let seller = sale.seller;
+    sale.refund(seller).unwrap();
+    assert_eq!(sale.state(), EscrowState::Refunded);
+    assert_eq!(sale.bank.lamports(&sale.buyer), buyer_before + PRICE);
+}
+
+#[test]
+fn the_buyer_cannot_take_the_deposit_back_after_delivery() {
+    let mut sale = Sale::delivered();
+    let buyer = sale.buyer;
+
+    let result = sale.refund(buyer);
+    assert_eq!(result, Err(anchor_error(EscrowError::Unauthorized)));
+    assert_eq!(sale.state(), EscrowState::Delivered);
+}
+
+#[test]
+fn strangers_cannot_refund() {
+    let mut sale = Sale::funded();
+    let stranger = sale.bank.wallet(1);
+
+    let result = sale.refund(stranger);
+    assert_eq!(result, Err(anchor_error(EscrowError::Unauthorized)));
+    assert_eq!(sale.state(), EscrowState::Funded);
+}
+
+#[test]
+fn settled_escrows_cannot_be_refunded() {
+    let mut sale = Sale::delivered();
+    sale.confirm().unwrap();
+    let seller = sale.seller;
+
+    let result = sale.refund(seller);
+    assert_eq!(result, Err(anchor_error(EscrowError::InvalidEscrowState)));
+}
+
+#[test]
+fn a_seller_can_run_several_escrows_at_once() {
+    let mut sale = Sale::delivered();
+    let seller = sale.seller;
+    let second_buyer = sale.bank.wallet(1);
+    let second = list(&mut sale.bank, seller, ESCROW_ID + 1);
+    fund(&mut sale.bank, second, second_buyer).unwrap();
+
+    sale.confirm().unwrap();
+
+    assert_eq!(sale.state(), EscrowState::Completed);
+    let second_escrow: Escrow = sale.bank.get(&second);
+    assert_eq!(second_escrow.id, ESCROW_ID + 1);
+    assert_eq!(second_escrow.buyer, second_buyer);
+    assert_eq!(second_escrow.state, EscrowState::Funded);
+}
+
+#[test]
+fn cancelling_an_unfunded_listing_returns_its_rent() {
+    let mut sale = Sale::listed();
+    let rent = sale.bank.lamports(&sale.escrow);
+    let seller_before = sale.bank.lamports(&sale.seller);
+
+    sale.cancel().unwrap();
+
+    assert!(sale.bank.account(&sale.escrow).is_none());
+    assert_eq!(sale.bank.lamports(&sale.seller), seller_before + rent);
+}
+
+#[test]
+fn funded_escrows_cannot_be_cancelled() {
+    let mut sale = Sale::funded();
+
+    assert_eq!(sale.cancel(), Err(anchor_error(EscrowError::InvalidEscrowState)));
+    assert_eq!(sale.state(), EscrowState::Funded);
+}
+
+#[test]
+fn escrows_cannot_be_closed_before_they_settle() {
+    let mut sale = Sale::funded();
+    assert_eq!(sale.close(), Err(anchor_error(EscrowError::InvalidEscrowState)));
+
+    let mut sale = Sale::delivered();
+    assert_eq!(sale.close(), Err(anchor_error(EscrowError::InvalidEscrowState)));
+    assert_eq!(sale.state(), EscrowState::Delivered);
+}
+
+#[test]
+fn closing_a_completed_escrow_returns_its_rent() {
+    let mut sale = Sale::delivered();
+    sale.confirm().unwrap();
+    let rent = sale.bank.lamports(&sale.escrow);
+    let seller_before = sale.bank.lamports(&sale.seller);
+
+    sale.close().unwrap();
+
+    assert!(sale.bank.account(&sale.escrow).is_none());
+    assert_eq!(sale.bank.lamports(&sale.seller), seller_before + rent);
+}
+
+#[test]
+fn closing_a_refunded_escrow_returns_its_rent() {
+    let mut sale = Sale::funded();
+    let buyer = sale.buyer;
+    sale.refund(buyer).unwrap();
+    let rent = sale.bank.lamports(&sale.escrow);
+    let seller_before = sale.bank.lamports(&sale.seller);
+
+    sale.close().unwrap();
+
+    assert!(sale.bank.account(&sale.escrow).is_none());
+    assert_eq!(sale.bank.lamports(&sale.seller), seller_before + rent);
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, pda, Bank};
+use my_escrow_project::{accounts, instruction, EscrowError, TokenEscrow};
+
+const ESCROW_ID: u64 = 7;
+const AMOUNT_A: u64 = 1_000;
+const AMOUNT_B: u64 = 250_000;
+
+/// A maker holding mint A, a taker holding mint B, and an offer of `AMOUNT_A` for `AMOUNT_B`.
+struct Offer {
+    bank: Bank,
+    maker: Pubkey,
+    taker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    maker_token_a: Pubkey,
+    maker_token_b: Pubkey,
+    taker_token_a: Pubkey,
+    taker_token_b: Pubkey,
+    token_escrow: Pubkey,
+    vault: Pubkey,
+}
+
+impl Offer {
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let maker = bank.wallet(1);
+        let taker = bank.wallet(1);
+        let mint_a = bank.create_mint(&maker, 0);
+        let mint_b = bank.create_mint(&taker, 6);
+        let token_escrow = pda(&[b"token_escrow", maker.as_ref(), &ESCROW_ID.to_le_bytes()]);
+
+        Self {
+            maker_token_a: bank.create_token_account(&mint_a, &maker, AMOUNT_A),
+            maker_token_b: bank.create_token_account(&mint_b, &maker, 0),
+            taker_token_a: bank.create_token_account(&mint_a, &taker, 0),
+            taker_token_b: bank.create_token_account(&mint_b, &taker, AMOUNT_B),
+            vault: pda(&[b"vault", token_escrow.as_ref()]),
+            bank,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            token_escrow,
+        }
+    }
+
+    fn make_accounts(&self) -> accounts::MakeTokenEscrow {
+        accounts::MakeTokenEscrow {
+            token_escrow: self.token_escrow,
+            vault: self.vault,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            maker_token_a: self.maker_token_a,
+            maker: self.maker,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    fn make(&mut self, accounts: accounts::MakeTokenEscrow, amount_a: u64) -> Result<(), ProgramError> {
+        let maker = self.maker;
+        self.bank.send(
+            accounts,
+            instruction::MakeTokenEscrow {
+                escrow_id: ESCROW_ID,
+                amount_a,
+                amount_b: AMOUNT_B,
+            },
+            &[&maker],
+        )
+    }
+
+    /// An offer whose vault already holds the maker's deposit.
+    fn made() -> Self {
+        let mut offer = Self::new();
+        let accounts = offer.make_accounts();
+        offer.make(accounts, AMOUNT_A).unwrap();
+        offer
+    }
+
+    fn take_accounts(&self) -> accounts::TakeTokenEscrow {
+        accounts::TakeTokenEscrow {
+            token_escrow: self.token_escrow,
+            vault: self.vault,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            taker_token_a: self.taker_token_a,
+            taker_token_b: self.taker_token_b,
+            maker_token_b: self.maker_token_b,
+            maker: self.maker,
+            taker: self.taker,
+            token_program: anchor_spl::token::ID,
+        }
+    }
+
+    fn take(&mut self, accounts: accounts::TakeTokenEscrow) -> Result<(), ProgramError> {
+        let taker = self.taker;
+        self.bank.send(accounts, instruction::TakeTokenEscrow {}, &[&taker])
+    }
+
+    fn cancel(&mut self, maker: Pubkey, maker_token_a: Pubkey) -> Result<(), ProgramError> {
+        self.bank.send(
+            accounts::CancelTokenEscrow {
+                token_escrow: self.token_escrow,
+                vault: self.vault,
+                mint_a: self.mint_a,
+                maker_token_a,
+                maker,
+                token_program: anchor_spl::token::ID,
+            },
+            instruction::CancelTokenEscrow {},
+            &[&maker],
+        )
+    }
+}
+
+#[test]
+fn make_token_escrow_moves_the_deposit_into_the_vault() {
+    let offer = Offer::made();
+
+    assert_eq!(offer.bank.token_balance(&offer.vault), AMOUNT_A);
+    assert_eq!(offer.bank.token_balance(&offer.maker_token_a), 0);
+    let token_escrow: TokenEscrow = offer.bank.get(&offer.token_escrow);
+    assert_eq!(token_escrow.maker, offer.maker);
+    assert_eq!(token_escrow.mint_a, offer.mint_a);
+    assert_eq!(token_escrow.mint_b, offer.mint_b);
+    assert_eq!(token_escrow.amount_a, AMOUNT_A);
+    assert_eq!(token_escrow.amount_b, AMOUNT_B);
+}
+
+#[test]
+fn make_token_escrow_rejects_zero_amounts() {
+    let mut offer = Offer::new();
+    let accounts = offer.make_accounts();
+
+    let result = offer.make(accounts, 0);
+    assert_eq!(result, Err(anchor_error(EscrowError::InvalidAmount)));
+}
+
+#[test]
+fn make_token_escrow_rejects_a_deposit_account_in_another_mint() {
+    let mut offer = Offer::new();
+    let mut accounts = offer.make_accounts();
+    accounts.maker_token_a = offer.maker_token_b;
+
+    let result = offer.make(accounts, AMOUNT_A);
+    assert_eq!(result, Err(anchor_error(EscrowError::InvalidTokenAccount)));
+}
+
+#[test]
+fn taking_swaps_both_sides_and_closes_the_vault_and_escrow() {
+    let mut offer = Offer::made();
+    let rent = offer.bank.lamports(&offer.vault) + offer.bank.lamports(&offer.token_escrow);
+    let maker_before = offer.bank.lamports(&offer.maker);
+
+    let accounts = offer.take_accounts();
+    offer.take(accounts).unwrap();
+
+    assert_eq!(offer.bank.token_balance(&offer.taker_token_a), AMOUNT_A);
+    assert_eq!(offer.bank.token_balance(&offer.taker_token_b), 0);
+    assert_eq!(offer.bank.token_balance(&offer.maker_token_b), AMOUNT_B);
+    assert!(offer.bank.account(&offer.vault).is_none());
+    assert!(offer.bank.account(&offer.token_escrow).is_none());
+    assert_eq!(offer.bank.lamports(&offer.maker), maker_before + rent);
+}
+
+#[test]
+fn taking_rejects_token_accounts_in_the_wrong_mint() {
+    let mut offer = Offer::made();
+
+    let mut accounts = offer.take_accounts();
+    accounts.taker_token_a = offer.taker_token_b;
+    assert_eq!(offer.take(accounts), Err(anchor_error(EscrowError::InvalidTokenAccount)));
+
+    let mut accounts = offer.take_accounts();
+    accounts.taker_token_b = offer.taker_token_a;
+    assert_eq!(offer.take(accounts), Err(anchor_error(EscrowError::InvalidTokenAccount)));
+
+    let mut accounts = offer.take_accounts();
+    accounts.maker_token_b = offer.maker_token_a;
+    assert_eq!(offer.take(accounts), Err(anchor_error(EscrowError::InvalidTokenAccount)));
+
+    assert_eq!(offer.bank.token_balance(&offer.vault), AMOUNT_A);
+}
+
+#[test]
+fn taking_rejects_a_payment_account_the_maker_does_not_own() {
+    let mut offer = Offer::made();
+    let mint_b = offer.mint_b;
+    let taker = offer.taker;
+    let diverted = offer.bank.create_token_account(&mint_b, &taker, 0);
+
+    let mut accounts = offer.take_accounts();
+    accounts.maker_token_b = diverted;
+    assert_eq!(offer.take(accounts), Err(anchor_error(EscrowError::InvalidTokenAccount)));
+}
+
+#[test]
+fn taking_rejects_mints_other_than_the_offers() {
+    let mut offer = Offer::made();
+    let taker = offer.taker;
+    let other_mint = offer.bank.create_mint(&taker, 6);
+    let other_token = offer.bank.create_token_account(&other_mint, &taker, AMOUNT_B);
+    let maker = offer.maker;
+    let maker_other_token = offer.bank.create_token_account(&other_mint, &maker, 0);
+
+    let mut accounts = offer.take_accounts();
+    accounts.mint_b = other_mint;
+    accounts.taker_token_b = other_token;
+    accounts.maker_token_b = maker_other_token;
+    assert_eq!(offer.take(accounts), Err(anchor_error(ErrorCode::ConstraintHasOne)));
+    assert_eq!(offer.bank.token_balance(&offer.vault), AMOUNT_A);
+}
+
+#[test]
+fn cancelling_returns_the_deposit_and_closes_the_vault_and_escrow() {
+    let mut offer = Offer::made();
+    let rent = offer.bank.lamports(&offer.vault) + offer.bank.lamports(&offer.token_escrow);
+    let maker_before = offer.bank.lamports(&offer.maker);
+
+    let (maker, maker_token_a) = (offer.maker, offer.maker_token_a);
+    offer.cancel(maker, maker_token_a).unwrap();
+
+    assert_eq!(offer.bank.token_balance(&offer.maker_token_a), AMOUNT_A);
+    assert!(offer.bank.account(&offer.vault).is_none());
+    assert!(offer.bank.account(&offer.token_escrow).is_none());
+    assert_eq!(offer.bank.lamports(&offer.maker), maker_before + rent);
+}
+
+#[test]
+fn only_the_maker_can_cancel() {
+    let mut offer = Offer::made();
+    let (taker, taker_token_a) = (offer.taker, offer.taker_token_a);
+
+    let result = offer.cancel(taker, taker_token_a);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+    assert_eq!(offer.bank.token_balance(&offer.vault), AMOUNT_A);
+}
+
+#[test]
+fn cancelling_rejects_a_refund_account_in_another_mint() {
+    let mut offer = Offer::made();
+
+    let (maker, maker_token_b) = (offer.maker, offer.maker_token_b);
+    let result = offer.cancel(maker, maker_token_b);
+    assert_eq!(result, Err(anchor_error(EscrowError::InvalidTokenAccount)));
+    assert_eq!(offer.bank.token_balance(&offer.vault), AMOUNT_A);
+}
@@ -15,6 +15,7 @@ describe("my_escrow_project", () => {
 
   let escrowPda: PublicKey;
   let bump: number;
+  const escrowId = new anchor.BN(1);
 
   before(async() => {
     seller = Keypair.generate();
@@ -23,7 +24,7 @@ describe("my_escrow_project", () => {
     escrowPda = Keypair.generate().publicKey;
 
     await airdrop(provider.connection, seller.publicKey, 1 * LAMPORTS_PER_SOL);
-    await airdrop(provider.connection, buyer.publicKey, 1 * LAMPORTS_PER_SOL);
+    await airdrop(provider.connection, buyer.publicKey, 2 * LAMPORTS_PER_SOL);
 
     console.log("Seller: ", seller.publicKey.toString());
     console.log("Buyer: ", buyer.publicKey.toString());
@@ -31,7 +32,7 @@ describe("my_escrow_project", () => {
 
   it("Initialize an escrow", async () => {
     [escrowPda, bump] = PublicKey.findProgramAddressSync(
-      [Buffer.from("escrow"), seller.publicKey.toBuffer()],
+      [Buffer.from("escrow"), seller.publicKey.toBuffer(), escrowId.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
 
@@ -40,7 +41,7 @@ describe("my_escrow_project", () => {
     const amount = 1 * LAMPORTS_PER_SOL;
     const item_details = "Pen";
     const tx = await program.methods
-    .initializeEscrow(new anchor.BN(amount), item_details)
+    .initializeEscrow(escrowId, new anchor.BN(amount), item_details)
     .accountsPartial({
       escrow: escrowPda,
       seller: seller.publicKey,
@@ -53,17 +54,121 @@ describe("my_escrow_project", () => {
 
     const escrowAccount = await program.account.escrow.fetch(escrowPda);
 
+    assert.equal(escrowAccount.id.toNumber(), escrowId.toNumber());
     assert.equal(escrowAccount.amount.toNumber(), amount);
     assert.equal(escrowAccount.itemDetails, item_details);
-    assert.equal(escrowAccount.isActive, false);
+    assert.deepEqual(escrowAccount.state, { created: {} });
 
     console.log("Escrow created successfully");
 
   });
+
+  it("Buyer funds the escrow", async () => {
+    await program.methods
+    .fundEscrow()
+    .accountsPartial({
+      escrow: escrowPda,
+      buyer: buyer.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([buyer])
+    .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+
+    assert.equal(escrowAccount.buyer.toString(), buyer.publicKey.toString());
+    assert.deepEqual(escrowAccount.state, { funded: {} });
+  });
+
+  it("Buyer cannot release funds before delivery", async () => {
+    try {
+      await program.methods
+      .confirmDelivery()
+      .accountsPartial({
+        escrow: escrowPda,
+        seller: seller.publicKey,
+        buyer: buyer.publicKey
+      })
+      .signers([buyer])
+      .rpc();
+      assert.fail("confirmDelivery should fail before the seller delivers");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "InvalidEscrowState");
+    }
+  });
+
+  it("Seller delivers and buyer confirms", async () => {
+    await program.methods
+    .markDelivered()
+    .accountsPartial({
+      escrow: escrowPda,
+      seller: seller.publicKey
+    })
+    .signers([seller])
+    .rpc();
+
+    const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+
+    await program.methods
+    .confirmDelivery()
+    .accountsPartial({
+      escrow: escrowPda,
+      seller: seller.publicKey,
+      buyer: buyer.publicKey
+    })
+    .signers([buyer])
+    .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+    const sellerAfter = await provider.connection.getBalance(seller.publicKey);
+
+    assert.deepEqual(escrowAccount.state, { completed: {} });
+    assert.equal(sellerAfter - sellerBefore, escrowAccount.amount.toNumber());
+  });
+
+  it("Seller can run another escrow alongside the first", async () => {
+    const secondId = new anchor.BN(2);
+    const [secondPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), seller.publicKey.toBuffer(), secondId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+    .initializeEscrow(secondId, new anchor.BN(LAMPORTS_PER_SOL / 2), "Notebook")
+    .accountsPartial({
+      escrow: secondPda,
+      seller: seller.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([seller])
+    .rpc();
+
+    const secondAccount = await program.account.escrow.fetch(secondPda);
+    assert.equal(secondAccount.itemDetails, "Notebook");
+    assert.deepEqual(secondAccount.state, { created: {} });
+  });
+
+  it("Seller closes the completed escrow and recovers its rent", async () => {
+    const rent = await provider.connection.getBalance(escrowPda);
+    const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+
+    await program.methods
+    .closeEscrow()
+    .accountsPartial({
+      escrow: escrowPda,
+      seller: seller.publicKey
+    })
+    .signers([seller])
+    .rpc();
+
+    const sellerAfter = await provider.connection.getBalance(seller.publicKey);
+    assert.isNull(await provider.connection.getAccountInfo(escrowPda));
+    assert.isAbove(sellerAfter, sellerBefore + rent - 10_000);
+  });
 });
 
 async function airdrop(connection: Connection, creator: PublicKey, amount: number) {
-  const signature = await connection.requestAirdrop(creator, amount * LAMPORTS_PER_SOL);
+  const signature = await connection.requestAirdrop(creator, amount);
   const latestBlockHash = await connection.getLatestBlockhash();
 
   const tx = await connection.confirmTransaction({
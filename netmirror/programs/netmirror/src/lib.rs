@@ -1,235 +1,3498 @@
-use anchor_lang::{prelude::*, solana_program::example_mocks::solana_sdk::system_program};
+// `#[program]` expands to a call to the deprecated `AccountInfo::realloc` in anchor-lang 0.31.
+#![allow(deprecated)]
+
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
 
 declare_id!("32Fi4XBiBnYk2nwNVQ7hhrefPjCVAgcxV2LSB3dvh86t");
 
+pub const MAX_TITLE_LEN: usize = 100;
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+pub const MAX_VIDEO_URL_LEN: usize = 200;
+pub const MAX_GENRE_LEN: usize = 32;
+
 #[program]
 pub mod netmirror {
     use anchor_lang::system_program;
 
     use super::*;
 
+    pub fn initialize_platform(ctx: Context<InitializePlatform>, epoch_duration: i64) -> Result<()> {
+        require!(epoch_duration > 0, NetMirrorError::InvalidEpochDuration);
+
+        // The treasury PDA holds no data, but it must stay rent exempt once it starts paying out.
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        let treasury_lamports = ctx.accounts.treasury.lamports();
+        if treasury_lamports < rent_exempt_minimum {
+            let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+                from: ctx.accounts.admin.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            });
+            system_program::transfer(cpi_context, rent_exempt_minimum - treasury_lamports)?;
+        }
+
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.admin = ctx.accounts.admin.key();
+        platform_state.pending_admin = Pubkey::default();
+        platform_state.treasury = ctx.accounts.treasury.key();
+        platform_state.payment_mint = Pubkey::default();
+        platform_state.treasury_token_account = Pubkey::default();
+        platform_state.pending_treasury_token_account = Pubkey::default();
+        platform_state.treasury_change_unlocks_at = 0;
+        platform_state.attestor = Pubkey::default();
+        platform_state.paused = false;
+        platform_state.subscriptions_paused = false;
+        platform_state.uploads_paused = false;
+        platform_state.ratings_paused = false;
+        platform_state.movie_count = 0;
+        platform_state.epoch_duration = epoch_duration;
+        platform_state.view_cooldown = PlatformState::DEFAULT_VIEW_COOLDOWN;
+        platform_state.rental_fee_bps = PlatformState::DEFAULT_RENTAL_FEE_BPS;
+        platform_state.refund_policy = RefundPolicy::None;
+        platform_state.refund_grace_period = 0;
+        platform_state.trial_duration = 0;
+        platform_state.current_epoch = 0;
+        platform_state.reserved_revenue = 0;
+        platform_state.refund_reserve = 0;
+        platform_state.token_refund_reserve = 0;
+        platform_state.curators = Vec::new();
+        platform_state.moderators = Vec::new();
+        platform_state.treasury_bump = ctx.bumps.treasury;
+        platform_state.bump = ctx.bumps.platform_state;
+
+        let revenue_epoch = &mut ctx.accounts.revenue_epoch;
+        revenue_epoch.open(0, Clock::get()?.unix_timestamp, epoch_duration, ctx.bumps.revenue_epoch)?;
+        Ok(())
+    }
+
+    pub fn add_curator(ctx: Context<ManageCurators>, curator: Pubkey) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        require!(!platform_state.curators.contains(&curator), NetMirrorError::CuratorAlreadyAdded);
+        require!(
+            platform_state.curators.len() < PlatformState::MAX_CURATORS,
+            NetMirrorError::TooManyCurators
+        );
+        platform_state.curators.push(curator);
+        Ok(())
+    }
+
+    pub fn remove_curator(ctx: Context<ManageCurators>, curator: Pubkey) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        let index = platform_state
+            .curators
+            .iter()
+            .position(|key| *key == curator)
+            .ok_or(NetMirrorError::CuratorNotFound)?;
+        platform_state.curators.swap_remove(index);
+        Ok(())
+    }
+
+    pub fn add_moderator(ctx: Context<ManageModerators>, moderator: Pubkey) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        require!(
+            !platform_state.moderators.contains(&moderator),
+            NetMirrorError::ModeratorAlreadyAdded
+        );
+        require!(
+            platform_state.moderators.len() < PlatformState::MAX_MODERATORS,
+            NetMirrorError::TooManyModerators
+        );
+        platform_state.moderators.push(moderator);
+        Ok(())
+    }
+
+    pub fn remove_moderator(ctx: Context<ManageModerators>, moderator: Pubkey) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        let index = platform_state
+            .moderators
+            .iter()
+            .position(|key| *key == moderator)
+            .ok_or(NetMirrorError::ModeratorNotFound)?;
+        platform_state.moderators.swap_remove(index);
+        Ok(())
+    }
+
+    /// One-time setup; later changes go through `propose_treasury_token_account`.
+    pub fn configure_token_payment(ctx: Context<ConfigureTokenPayment>) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        require!(
+            platform_state.payment_mint == Pubkey::default(),
+            NetMirrorError::TokenPaymentAlreadyConfigured
+        );
+        platform_state.payment_mint = ctx.accounts.payment_mint.key();
+        platform_state.treasury_token_account = ctx.accounts.treasury_token_account.key();
+        Ok(())
+    }
+
+    pub fn set_pause_flags(
+        ctx: Context<SetPauseFlags>,
+        paused: bool,
+        subscriptions_paused: bool,
+        uploads_paused: bool,
+        ratings_paused: bool,
+    ) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.paused = paused;
+        platform_state.subscriptions_paused = subscriptions_paused;
+        platform_state.uploads_paused = uploads_paused;
+        platform_state.ratings_paused = ratings_paused;
+
+        emit!(PauseFlagsUpdated {
+            paused,
+            subscriptions_paused,
+            uploads_paused,
+            ratings_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// First half of an admin handover; proposing `Pubkey::default()` withdraws a pending proposal.
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.platform_state.pending_admin = new_admin;
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        let previous_admin = platform_state.admin;
+        platform_state.admin = ctx.accounts.new_admin.key();
+        platform_state.pending_admin = Pubkey::default();
+
+        emit!(AdminChanged {
+            previous_admin,
+            admin: platform_state.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Token payments are collected into whatever account the admin proposes here once it is
+    /// accepted. The account must be held by the treasury PDA so refunds can be paid from it, and
+    /// must be for the configured mint: token terms are refunded in the mint they were paid in.
+    ///
+    /// The change can only be accepted after `TREASURY_CHANGE_DELAY`, so subscribers can see it
+    /// coming.
+    pub fn propose_treasury_token_account(ctx: Context<ProposeTreasuryTokenAccount>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.pending_treasury_token_account = ctx.accounts.treasury_token_account.key();
+        platform_state.treasury_change_unlocks_at = current_time
+            .checked_add(PlatformState::TREASURY_CHANGE_DELAY)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+
+        emit!(TreasuryTokenAccountProposed {
+            treasury_token_account: platform_state.pending_treasury_token_account,
+            unlocks_at: platform_state.treasury_change_unlocks_at,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Switches to the proposed account and moves the old account's balance over to it.
+    pub fn accept_treasury_token_account(ctx: Context<AcceptTreasuryTokenAccount>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= ctx.accounts.platform_state.treasury_change_unlocks_at,
+            NetMirrorError::TreasuryChangeTimelocked
+        );
+
+        let balance = ctx.accounts.previous_treasury_token_account.amount;
+        if balance > 0 {
+            let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[ctx.accounts.platform_state.treasury_bump]]];
+            let cpi_context = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.previous_treasury_token_account.to_account_info(),
+                    mint: ctx.accounts.payment_mint.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer_checked(cpi_context, balance, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        let platform_state = &mut ctx.accounts.platform_state;
+        let previous_account = platform_state.treasury_token_account;
+        platform_state.treasury_token_account = ctx.accounts.treasury_token_account.key();
+        platform_state.pending_treasury_token_account = Pubkey::default();
+        platform_state.treasury_change_unlocks_at = 0;
+
+        emit!(TreasuryTokenAccountChanged {
+            previous_account,
+            treasury_token_account: platform_state.treasury_token_account,
+            payment_mint: platform_state.payment_mint,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Registers the signer trusted to attest viewers' regions and age tiers.
+    pub fn set_attestor(ctx: Context<SetAttestor>, attestor: Pubkey) -> Result<()> {
+        ctx.accounts.platform_state.attestor = attestor;
+        Ok(())
+    }
+
+    pub fn set_view_cooldown(ctx: Context<SetViewCooldown>, view_cooldown: i64) -> Result<()> {
+        require!(view_cooldown >= 0, NetMirrorError::InvalidViewCooldown);
+        ctx.accounts.platform_state.view_cooldown = view_cooldown;
+        Ok(())
+    }
+
+    pub fn set_rental_fee(ctx: Context<SetRentalFee>, rental_fee_bps: u16) -> Result<()> {
+        require!(rental_fee_bps <= PlatformState::MAX_BPS, NetMirrorError::InvalidRentalFee);
+        ctx.accounts.platform_state.rental_fee_bps = rental_fee_bps;
+        Ok(())
+    }
+
+    pub fn set_refund_policy(
+        ctx: Context<SetRefundPolicy>,
+        refund_policy: RefundPolicy,
+        refund_grace_period: i64,
+    ) -> Result<()> {
+        require!(refund_grace_period >= 0, NetMirrorError::InvalidGracePeriod);
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.refund_policy = refund_policy;
+        platform_state.refund_grace_period = refund_grace_period;
+        Ok(())
+    }
+
+    /// Sets how much of the treasury is held back for refunds: `refund_reserve` lamports are left
+    /// out of every epoch's revenue and `token_refund_reserve` tokens can't be withdrawn.
+    pub fn set_refund_reserve(
+        ctx: Context<SetRefundReserve>,
+        refund_reserve: u64,
+        token_refund_reserve: u64,
+    ) -> Result<()> {
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.refund_reserve = refund_reserve;
+        platform_state.token_refund_reserve = token_refund_reserve;
+        Ok(())
+    }
+
+    /// Moves token subscription revenue out of the treasury token account. Creator payouts are in
+    /// SOL only, so this is how token revenue leaves the platform.
+    pub fn withdraw_token_revenue(ctx: Context<WithdrawTokenRevenue>, amount: u64) -> Result<()> {
+        let platform_state = &ctx.accounts.platform_state;
+        let available = ctx
+            .accounts
+            .treasury_token_account
+            .amount
+            .saturating_sub(platform_state.token_refund_reserve);
+        require!(amount <= available, NetMirrorError::InsufficientTokenRevenue);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[platform_state.treasury_bump]]];
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.treasury_token_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.treasury.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer_checked(cpi_context, amount, ctx.accounts.payment_mint.decimals)?;
+
+        emit!(TokenRevenueWithdrawn {
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// A duration of zero turns free trials off.
+    pub fn set_trial_duration(ctx: Context<SetTrialDuration>, trial_duration: i64) -> Result<()> {
+        require!(trial_duration >= 0, NetMirrorError::InvalidTrialDuration);
+        ctx.accounts.platform_state.trial_duration = trial_duration;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_plan(
+        ctx: Context<CreatePlan>,
+        plan_id: u16,
+        name: String,
+        price: u64,
+        token_price: u64,
+        duration: i64,
+        max_streams: u8,
+        max_members: u8,
+    ) -> Result<()> {
+        require!(name.len() <= Plan::MAX_NAME_LEN, NetMirrorError::PlanNameTooLong);
+        require!(duration > 0, NetMirrorError::InvalidPlanDuration);
+        require!(max_streams > 0, NetMirrorError::InvalidMaxStreams);
+        require!(
+            max_members as usize <= UserAccount::MAX_MEMBERS,
+            NetMirrorError::InvalidMaxMembers
+        );
+
+        let plan = &mut ctx.accounts.plan;
+        plan.id = plan_id;
+        plan.name = name;
+        plan.price = price;
+        plan.token_price = token_price;
+        plan.duration = duration;
+        plan.max_streams = max_streams;
+        plan.max_members = max_members;
+        plan.is_active = true;
+        plan.bump = ctx.bumps.plan;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_plan(
+        ctx: Context<UpdatePlan>,
+        _plan_id: u16,
+        price: u64,
+        token_price: u64,
+        duration: i64,
+        max_streams: u8,
+        max_members: u8,
+        is_active: bool,
+    ) -> Result<()> {
+        require!(duration > 0, NetMirrorError::InvalidPlanDuration);
+        require!(max_streams > 0, NetMirrorError::InvalidMaxStreams);
+        require!(
+            max_members as usize <= UserAccount::MAX_MEMBERS,
+            NetMirrorError::InvalidMaxMembers
+        );
+
+        let plan = &mut ctx.accounts.plan;
+        plan.price = price;
+        plan.token_price = token_price;
+        plan.duration = duration;
+        plan.max_streams = max_streams;
+        plan.max_members = max_members;
+        plan.is_active = is_active;
+        Ok(())
+    }
+
     pub fn create_account(ctx: Context<CreateUser>) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
         let account = &mut ctx.accounts.user_account ;
         account.owner = ctx.accounts.owner.key();
-        account.is_subscribed = false;
         account.subscription_expiry = 0;
+        account.auto_renew = false;
+        account.plan = Pubkey::default();
+        account.members = Vec::new();
+        account.period_start = 0;
+        account.paid_until = 0;
+        account.paid_amount = 0;
+        account.paid_currency = PaymentCurrency::Sol;
+        account.trial_used = false;
+        account.region = 0;
+        account.age_tier = 0;
+        account.attested = false;
         account.bump = ctx.bumps.user_account;
+
+        emit!(AccountCreated {
+            user_account: account.key(),
+            owner: account.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Records the account's region (a bit index into `Movie::allowed_regions`) and age tier.
+    /// Household members watch under the owner's attestation.
+    pub fn attest_user(ctx: Context<AttestUser>, region: u8, age_tier: u8) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
+        require!(region < Movie::REGION_COUNT, NetMirrorError::InvalidRegion);
+
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.region = region;
+        user_account.age_tier = age_tier;
+        user_account.attested = true;
+
+        emit!(UserAttested {
+            user_account: user_account.key(),
+            attestor: ctx.accounts.attestor.key(),
+            region,
+            age_tier,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn add_member(ctx: Context<ManageMembers>, member: Pubkey) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        require!(member != user_account.owner, NetMirrorError::MemberAlreadyAdded);
+        require!(!user_account.members.contains(&member), NetMirrorError::MemberAlreadyAdded);
+        require!(
+            user_account.members.len() < ctx.accounts.plan.max_members as usize,
+            NetMirrorError::TooManyMembers
+        );
+        user_account.members.push(member);
+
+        emit!(MemberAdded {
+            user_account: user_account.key(),
+            member,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn remove_member(ctx: Context<ManageMembers>, member: Pubkey) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        let index = user_account
+            .members
+            .iter()
+            .position(|key| *key == member)
+            .ok_or(NetMirrorError::MemberNotFound)?;
+        user_account.members.remove(index);
+
+        emit!(MemberRemoved {
+            user_account: user_account.key(),
+            member,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Grants one free trial to an account that has never subscribed. Trials don't auto-renew and
+    /// have nothing to refund.
+    pub fn claim_trial(ctx: Context<ClaimTrial>) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let trial_duration = ctx.accounts.platform_state.trial_duration;
+        require!(trial_duration > 0, NetMirrorError::TrialsDisabled);
+
+        let user_account = &mut ctx.accounts.user_account;
+        require!(!user_account.trial_used, NetMirrorError::TrialAlreadyUsed);
+        require!(user_account.subscription_expiry == 0, NetMirrorError::TrialUnavailable);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        user_account.subscription_expiry = current_time
+            .checked_add(trial_duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        user_account.period_start = current_time;
+        user_account.paid_until = current_time;
+        user_account.paid_amount = 0;
+        user_account.auto_renew = false;
+        user_account.trial_used = true;
+
+        emit!(TrialStarted {
+            user_account: user_account.key(),
+            owner: user_account.owner,
+            expiry: user_account.subscription_expiry,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn subscribe(ctx: Context<Subscribe>, _plan_id: u16, currency: PaymentCurrency) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let plan = &ctx.accounts.plan;
+        require!(plan.is_active, NetMirrorError::PlanInactive);
+
+        let user_account = &mut ctx.accounts.user_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        // Refunds are paid in the currency the term was bought with, so a term can't mix the two.
+        if user_account.paid_until > current_time && user_account.paid_amount > 0 {
+            require!(
+                user_account.paid_currency == currency,
+                NetMirrorError::PaymentCurrencyMismatch
+            );
+        } else {
+            // Unpaid time from a trial or a gift is used up first, so the paid term starts after it.
+            let period_start = user_account.subscription_expiry.max(current_time);
+            user_account.period_start = period_start;
+            user_account.paid_until = period_start;
+            user_account.paid_amount = 0;
+            user_account.paid_currency = currency;
+        }
+        // Renewing before expiry stacks the new period right after the paid time, so the paid
+        // term stays contiguous and any gifted time still comes after it.
+        user_account.paid_until = user_account
+            .paid_until
+            .checked_add(plan.duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        user_account.subscription_expiry = user_account
+            .subscription_expiry
+            .max(current_time)
+            .checked_add(plan.duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        user_account.auto_renew = true;
+        user_account.plan = plan.key();
+        // Switching to a smaller household plan drops the most recently added members.
+        user_account.members.truncate(plan.max_members as usize);
+
+        let amount = match currency {
+            PaymentCurrency::Sol => {
+                let cost = plan.price;
+                let cpi_contxt = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer{
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info()
+                });
+                system_program::transfer(cpi_contxt, cost)?;
+                cost
+            }
+            PaymentCurrency::Token => {
+                require!(
+                    ctx.accounts.platform_state.payment_mint != Pubkey::default(),
+                    NetMirrorError::TokenPaymentNotConfigured
+                );
+                require!(plan.token_price > 0, NetMirrorError::TokenPaymentUnavailable);
+
+                let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+                    &ctx.accounts.payment_mint,
+                    &ctx.accounts.owner_token_account,
+                    &ctx.accounts.treasury_token_account,
+                    &ctx.accounts.token_program,
+                ) else {
+                    return err!(NetMirrorError::MissingTokenAccounts);
+                };
+
+                let cpi_context = CpiContext::new(token_program.to_account_info(), TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                });
+                token::transfer_checked(cpi_context, plan.token_price, mint.decimals)?;
+                plan.token_price
+            }
+        };
+        user_account.paid_amount = user_account
+            .paid_amount
+            .checked_add(amount)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+
+        emit!(Subscribed {
+            user_account: user_account.key(),
+            owner: user_account.owner,
+            plan: plan.key(),
+            currency,
+            amount,
+            expiry: user_account.subscription_expiry,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        user_account.require_active_subscription(current_time)?;
+        require!(user_account.auto_renew, NetMirrorError::SubscriptionAlreadyCancelled);
+        user_account.auto_renew = false;
+
+        // Without a refund, access runs until the paid period ends. A refund takes back the paid
+        // time that hasn't been used yet; gifted time is kept.
+        let platform_state = &ctx.accounts.platform_state;
+        let refund = user_account.refund_amount(
+            platform_state.refund_policy,
+            platform_state.refund_grace_period,
+            current_time,
+        );
+        if refund > 0 {
+            let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[platform_state.treasury_bump]]];
+            match user_account.paid_currency {
+                PaymentCurrency::Sol => {
+                    // Revenue already set aside for creators is not available for refunds.
+                    let available = ctx
+                        .accounts
+                        .treasury
+                        .lamports()
+                        .saturating_sub(Rent::get()?.minimum_balance(0))
+                        .saturating_sub(platform_state.reserved_revenue);
+                    require!(refund <= available, NetMirrorError::InsufficientTreasuryBalance);
+
+                    let cpi_context = CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.treasury.to_account_info(),
+                            to: ctx.accounts.owner.to_account_info(),
+                        },
+                        signer_seeds,
+                    );
+                    system_program::transfer(cpi_context, refund)?;
+                }
+                PaymentCurrency::Token => {
+                    let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+                        &ctx.accounts.payment_mint,
+                        &ctx.accounts.treasury_token_account,
+                        &ctx.accounts.owner_token_account,
+                        &ctx.accounts.token_program,
+                    ) else {
+                        return err!(NetMirrorError::MissingTokenAccounts);
+                    };
+
+                    require!(refund <= from.amount, NetMirrorError::InsufficientTreasuryBalance);
+
+                    let cpi_context = CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        TransferChecked {
+                            from: from.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.treasury.to_account_info(),
+                        },
+                        signer_seeds,
+                    );
+                    token::transfer_checked(cpi_context, refund, mint.decimals)?;
+                }
+            }
+
+            let unused = user_account.unused_paid_time(current_time);
+            user_account.subscription_expiry -= unused;
+            user_account.paid_until -= unused;
+            user_account.paid_amount = 0;
+        }
+
+        emit!(SubscriptionCancelled {
+            user_account: user_account.key(),
+            owner: user_account.owner,
+            currency: user_account.paid_currency,
+            refund,
+            expiry: user_account.subscription_expiry,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Pays for a plan period on someone else's account. Gifted time is credited to the recipient
+    /// but never refunded to them.
+    pub fn gift_subscription(ctx: Context<GiftSubscription>, _plan_id: u16, recipient: Pubkey) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let plan = &ctx.accounts.plan;
+        require!(plan.is_active, NetMirrorError::PlanInactive);
+
+        let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.gifter.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+        });
+        system_program::transfer(cpi_context, plan.price)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.add_unpaid_time(plan.key(), plan.duration, current_time)?;
+
+        emit!(SubscriptionGifted {
+            user_account: user_account.key(),
+            recipient,
+            gifter: ctx.accounts.gifter.key(),
+            plan: plan.key(),
+            amount: plan.price,
+            expiry: user_account.subscription_expiry,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Prepays a plan period that whoever knows the preimage of `code_hash` can redeem.
+    pub fn create_voucher(ctx: Context<CreateVoucher>, code_hash: [u8; 32], _plan_id: u16) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let plan = &ctx.accounts.plan;
+        require!(plan.is_active, NetMirrorError::PlanInactive);
+
+        let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.purchaser.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+        });
+        system_program::transfer(cpi_context, plan.price)?;
+
+        let voucher = &mut ctx.accounts.voucher;
+        voucher.code_hash = code_hash;
+        voucher.plan = plan.key();
+        // The buyer paid for the plan as it is now, so later plan updates don't change the voucher.
+        voucher.duration = plan.duration;
+        voucher.purchaser = ctx.accounts.purchaser.key();
+        voucher.amount = plan.price;
+        voucher.bump = ctx.bumps.voucher;
+
+        emit!(VoucherCreated {
+            voucher: voucher.key(),
+            purchaser: voucher.purchaser,
+            plan: voucher.plan,
+            amount: voucher.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
-    pub fn subscribe(ctx: Context<Subscribe>, duration: i64) -> Result<()> {
-        let userAccount = &mut ctx.accounts.user_account;
-        userAccount.is_subscribed = true;
+    /// Credits the voucher to the signer's account and returns its rent to the purchaser. The code
+    /// becomes public once redeemed, so each voucher works exactly once.
+    pub fn redeem_voucher(ctx: Context<RedeemVoucher>, code: Vec<u8>) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let voucher = &ctx.accounts.voucher;
+        require!(
+            hash(&code).to_bytes() == voucher.code_hash,
+            NetMirrorError::InvalidVoucherCode
+        );
+
         let current_time = Clock::get()?.unix_timestamp;
-        userAccount.subscription_expiry = current_time + duration;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.add_unpaid_time(voucher.plan, voucher.duration, current_time)?;
 
-        let cost = ctx.accounts.platform_state.subscription_price;
-        let cpi_contxt = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer{
-            from: ctx.accounts.owner.to_account_info(),
-            to: ctx.accounts.treasury.to_account_info()
+        emit!(VoucherRedeemed {
+            voucher: voucher.key(),
+            user_account: user_account.key(),
+            owner: user_account.owner,
+            plan: voucher.plan,
+            expiry: user_account.subscription_expiry,
+            timestamp: current_time,
         });
-        system_program::transfer(cpi_contxt, cost)?;
         Ok(())
     }
 
-    pub fn add_movie(ctx: Context<AddMovie>, title: String, description: String, video_url: String, genre: String) -> Result<()> {
+    pub fn create_genre(ctx: Context<CreateGenre>, genre_id: u16, name: String) -> Result<()> {
+        Genre::validate_name(&name)?;
+
+        let genre = &mut ctx.accounts.genre;
+        genre.id = genre_id;
+        genre.name = name;
+        genre.movie_count = 0;
+        genre.indexed_count = 0;
+        genre.is_active = true;
+        genre.bump = ctx.bumps.genre;
+
+        let genre_name = &mut ctx.accounts.genre_name;
+        genre_name.genre_id = genre_id;
+        genre_name.bump = ctx.bumps.genre_name;
+        Ok(())
+    }
+
+    pub fn update_genre(ctx: Context<UpdateGenre>, _genre_id: u16, is_active: bool) -> Result<()> {
+        ctx.accounts.genre.is_active = is_active;
+        Ok(())
+    }
+
+    /// Moves the genre's name reservation over to `name`, which must not be taken by another genre.
+    pub fn rename_genre(ctx: Context<RenameGenre>, _genre_id: u16, name: String) -> Result<()> {
+        Genre::validate_name(&name)?;
+
+        let genre = &mut ctx.accounts.genre;
+        genre.name = name;
+
+        let genre_name = &mut ctx.accounts.new_genre_name;
+        genre_name.genre_id = genre.id;
+        genre_name.bump = ctx.bumps.new_genre_name;
+        Ok(())
+    }
+
+    pub fn add_movie(
+        ctx: Context<AddMovie>,
+        title: String,
+        description: String,
+        video_url: String,
+        genre_id: u16,
+    ) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        Movie::validate_title(&title)?;
+        Movie::validate_description(&description)?;
+        Movie::validate_video_url(&video_url)?;
+
+        let platform_state = &mut ctx.accounts.platform_state;
         let movie = &mut ctx.accounts.movie;
+        movie.id = platform_state.movie_count;
+        platform_state.movie_count = platform_state
+            .movie_count
+            .checked_add(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
         movie.title = title;
         movie.description = description;
-        movie.added_by = ctx.accounts.admin.key();
+        movie.added_by = ctx.accounts.authority.key();
         movie.total_views = 0;
-        movie.genre = genre;
+        movie.unique_viewers = 0;
+        movie.genre_id = genre_id;
+        movie.genre_page = ctx.accounts.genre.index_movie(
+            &mut ctx.accounts.genre_page,
+            ctx.bumps.genre_page,
+            movie.id,
+        )?;
         movie.video_url = video_url;
         movie.rating_count = 0;
         movie.rating_sum = 0;
+        movie.rental_price = 0;
+        movie.rental_duration = 0;
+        movie.is_premium = false;
+        movie.content_rating = 0;
+        movie.allowed_regions = Movie::ALL_REGIONS;
         movie.bump = ctx.bumps.movie;
+
+        emit!(MovieAdded {
+            movie: movie.key(),
+            added_by: movie.added_by,
+            title: movie.title.clone(),
+            genre_id: movie.genre_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Rents a movie for its rental window; renting again before it lapses extends the window.
+    /// The platform fee goes to the treasury and the rest to the uploader.
+    pub fn rent_movie(ctx: Context<RentMovie>) -> Result<()> {
+        ctx.accounts.platform_state.require_subscriptions_enabled()?;
+
+        let movie = &ctx.accounts.movie;
+        require!(movie.rental_price > 0, NetMirrorError::RentalUnavailable);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let rental = &mut ctx.accounts.rental;
+        if rental.renter == Pubkey::default() {
+            rental.renter = ctx.accounts.renter.key();
+            rental.movie = movie.key();
+            rental.bump = ctx.bumps.rental;
+        }
+        rental.expires_at = rental
+            .expires_at
+            .max(current_time)
+            .checked_add(movie.rental_duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+
+        let platform_fee = (movie.rental_price as u128 * ctx.accounts.platform_state.rental_fee_bps as u128
+            / PlatformState::MAX_BPS as u128) as u64;
+        let creator_share = movie.rental_price - platform_fee;
+        for (to, amount) in [
+            (ctx.accounts.treasury.to_account_info(), platform_fee),
+            (ctx.accounts.added_by.to_account_info(), creator_share),
+        ] {
+            if amount > 0 {
+                let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+                    from: ctx.accounts.renter.to_account_info(),
+                    to,
+                });
+                system_program::transfer(cpi_context, amount)?;
+            }
+        }
+
+        emit!(MovieRented {
+            movie: movie.key(),
+            renter: rental.renter,
+            price: movie.rental_price,
+            platform_fee,
+            expires_at: rental.expires_at,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
+    /// Viewers get in with an active rental or a pass for this movie. Titles that aren't premium
+    /// are also open to subscribers and holders of a catalog-wide pass.
     pub fn watch_movie(ctx: Context<WatchMovie>) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
+        let current_time = Clock::get()?.unix_timestamp;
         let movie = &mut ctx.accounts.movie;
-        let user = &mut ctx.accounts.user_account;
-        if (user.is_subscribed == false) {
-            return err!(NetMirrorError::UserNotSubscribed);
+        movie.require_watch_access(
+            &movie.key(),
+            &ctx.accounts.viewer.key(),
+            ctx.accounts.user_account.as_deref(),
+            ctx.accounts.rental.as_deref(),
+            (ctx.accounts.access_pass.as_deref(), ctx.accounts.pass_token_account.as_deref()),
+            current_time,
+        )?;
+
+        let watch_record = &mut ctx.accounts.watch_record;
+        let first_view = watch_record.viewer == Pubkey::default();
+        if first_view {
+            watch_record.viewer = ctx.accounts.viewer.key();
+            watch_record.movie = movie.key();
+            watch_record.first_watched_at = current_time;
+            watch_record.bump = ctx.bumps.watch_record;
+            movie.unique_viewers += 1;
         }
-        movie.total_views += 1;
+        // Repeat views inside the cooldown are remembered in the history but don't count towards
+        // rankings or creator payouts.
+        let counted = first_view
+            || current_time - watch_record.last_counted_at >= ctx.accounts.platform_state.view_cooldown;
+        watch_record.last_watched_at = current_time;
+        watch_record.view_count += 1;
+
+        // The stats account may have just been created for a view that doesn't count; it's filled
+        // in either way so it never holds a blank creator or bump.
+        let revenue_epoch = &mut ctx.accounts.revenue_epoch;
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        if creator_stats.creator == Pubkey::default() {
+            creator_stats.epoch = revenue_epoch.index;
+            creator_stats.creator = movie.added_by;
+            creator_stats.bump = ctx.bumps.creator_stats;
+        }
+
+        if counted {
+            watch_record.last_counted_at = current_time;
+            movie.total_views += 1;
+            creator_stats.views += 1;
+            revenue_epoch.total_views += 1;
+        }
+
+        emit!(MovieWatched {
+            movie: movie.key(),
+            viewer: ctx.accounts.viewer.key(),
+            total_views: movie.total_views,
+            unique_viewers: movie.unique_viewers,
+            counted,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
-    pub fn update_movie(ctx: Context<UpdateMovie>, title: String) -> Result<()> {
-        let movie = &mut ctx.accounts.movie;
-        movie.title = title;
+    /// Stores where the viewer stopped so clients can offer "continue watching". Anyone who may
+    /// still watch the movie can save their position, with the same accounts as `watch_movie`.
+    pub fn update_progress(ctx: Context<UpdateProgress>, position_secs: u32) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let movie = &ctx.accounts.movie;
+        movie.require_watch_access(
+            &movie.key(),
+            &ctx.accounts.viewer.key(),
+            ctx.accounts.user_account.as_deref(),
+            ctx.accounts.rental.as_deref(),
+            (ctx.accounts.access_pass.as_deref(), ctx.accounts.pass_token_account.as_deref()),
+            current_time,
+        )?;
+
+        let watch_record = &mut ctx.accounts.watch_record;
+        watch_record.resume_position_secs = position_secs;
+        watch_record.last_watched_at = current_time;
+
+        emit!(WatchProgressUpdated {
+            movie: watch_record.movie,
+            viewer: watch_record.viewer,
+            position_secs,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
-    pub fn delete_movie(ctx: Context<DeleteMovie>) -> Result<()> {
+    /// Closes the current billing epoch once it has run its course and opens the next one.
+    ///
+    /// Everything in the treasury that is not already owed to creators or held back as the refund
+    /// reserve becomes the epoch's revenue. If nothing was watched, the balance rolls over into the
+    /// next epoch instead.
+    pub fn close_epoch(ctx: Context<CloseEpoch>) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let platform_state = &mut ctx.accounts.platform_state;
+        let revenue_epoch = &mut ctx.accounts.revenue_epoch;
+        require!(current_time >= revenue_epoch.end_time, NetMirrorError::EpochStillOpen);
+
+        if revenue_epoch.total_views > 0 {
+            let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+            revenue_epoch.revenue = ctx
+                .accounts
+                .treasury
+                .lamports()
+                .saturating_sub(rent_exempt_minimum)
+                .saturating_sub(platform_state.reserved_revenue)
+                .saturating_sub(platform_state.refund_reserve);
+            platform_state.reserved_revenue = platform_state
+                .reserved_revenue
+                .checked_add(revenue_epoch.revenue)
+                .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        }
+        revenue_epoch.is_closed = true;
+
+        platform_state.current_epoch = platform_state
+            .current_epoch
+            .checked_add(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        ctx.accounts.next_epoch.open(
+            platform_state.current_epoch,
+            current_time,
+            platform_state.epoch_duration,
+            ctx.bumps.next_epoch,
+        )?;
+
+        emit!(EpochClosed {
+            epoch: revenue_epoch.index,
+            revenue: revenue_epoch.revenue,
+            total_views: revenue_epoch.total_views,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
-    pub fn rate_movie(ctx: Context<RateMovie>, rating_count: u64) -> Result<()> {
-        let movie = &mut ctx.accounts.movie;
-        let review = &mut ctx.accounts.review;
-        movie.rating_count == 1;
-        movie.rating_sum += review.rating;
-        review.reviewer = ctx.accounts.user_account.key();
+    /// Pays an uploader their share of a closed epoch's revenue, pro rata to their movies' views.
+    pub fn claim_creator_revenue(ctx: Context<ClaimCreatorRevenue>, _epoch_index: u64) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
+
+        let revenue_epoch = &mut ctx.accounts.revenue_epoch;
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        require!(revenue_epoch.is_closed, NetMirrorError::EpochStillOpen);
+        require!(!revenue_epoch.released, NetMirrorError::EpochReleased);
+        require!(!creator_stats.has_claimed, NetMirrorError::RevenueAlreadyClaimed);
+
+        let share = (revenue_epoch.revenue as u128)
+            .checked_mul(creator_stats.views as u128)
+            .and_then(|amount| amount.checked_div(revenue_epoch.total_views as u128))
+            .ok_or(NetMirrorError::ArithmeticOverflow)? as u64;
+        require!(share > 0, NetMirrorError::NothingToClaim);
+
+        let treasury_bump = ctx.accounts.platform_state.treasury_bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"treasury", &[treasury_bump]]];
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.treasury.to_account_info(),
+                to: ctx.accounts.creator.to_account_info(),
+            },
+            signer_seeds,
+        );
+        system_program::transfer(cpi_context, share)?;
+
+        creator_stats.has_claimed = true;
+        revenue_epoch.claimed = revenue_epoch
+            .claimed
+            .checked_add(share)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.reserved_revenue = platform_state.reserved_revenue.saturating_sub(share);
+
+        emit!(CreatorRevenueClaimed {
+            epoch: revenue_epoch.index,
+            creator: creator_stats.creator,
+            views: creator_stats.views,
+            amount: share,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
-}
 
-#[error_code]
-pub enum NetMirrorError {
-    #[msg("User is not subscribed")]
-    UserNotSubscribed,
-}
+    /// Returns what is still reserved for a closed epoch to the treasury's free balance once its
+    /// claim window has passed: unclaimed shares and the rounding dust of the pro-rata split.
+    /// Creators can no longer claim from the epoch afterwards.
+    pub fn release_epoch_revenue(ctx: Context<ReleaseEpochRevenue>, _epoch_index: u64) -> Result<()> {
+        ctx.accounts.platform_state.require_unpaused()?;
 
-#[account]
-pub struct UserAccount {
-    pub owner: Pubkey,
-    pub is_subscribed: bool,
-    pub subscription_expiry: i64,
-    pub bump: u8
-}
+        let current_time = Clock::get()?.unix_timestamp;
+        let revenue_epoch = &mut ctx.accounts.revenue_epoch;
+        require!(revenue_epoch.is_closed, NetMirrorError::EpochStillOpen);
+        require!(!revenue_epoch.released, NetMirrorError::EpochReleased);
+        let claims_close_at = revenue_epoch
+            .end_time
+            .checked_add(RevenueEpoch::CLAIM_WINDOW)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        require!(current_time >= claims_close_at, NetMirrorError::ClaimWindowOpen);
 
-#[account]
-pub struct Movie {
-    pub title: String,
-    pub description: String,
-    pub video_url: String,
-    pub genre: String,
-    pub added_by: Pubkey,
-    pub total_views: u64,
-    pub rating_sum: u64,
-    pub rating_count: u64,
-    pub bump: u8 
-}
+        let unclaimed = revenue_epoch.revenue.saturating_sub(revenue_epoch.claimed);
+        revenue_epoch.released = true;
+        let platform_state = &mut ctx.accounts.platform_state;
+        platform_state.reserved_revenue = platform_state.reserved_revenue.saturating_sub(unclaimed);
 
-#[account]
-pub struct PlatformState {
-    pub admin: Pubkey,
-    pub treasury: Pubkey,
-    pub subscription_price: u64,
-    pub bump: u8
-}
+        emit!(EpochRevenueReleased {
+            epoch: revenue_epoch.index,
+            amount: unclaimed,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Updates any subset of the movie's metadata; fields passed as `None` are left unchanged.
+    pub fn update_movie(
+        ctx: Context<UpdateMovie>,
+        title: Option<String>,
+        description: Option<String>,
+        video_url: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        let movie = &mut ctx.accounts.movie;
+        if let Some(title) = title {
+            Movie::validate_title(&title)?;
+            movie.title = title;
+        }
+        if let Some(description) = description {
+            Movie::validate_description(&description)?;
+            movie.description = description;
+        }
+        if let Some(video_url) = video_url {
+            Movie::validate_video_url(&video_url)?;
+            movie.video_url = video_url;
+        }
+
+        emit!(MovieUpdated {
+            movie: movie.key(),
+            updated_by: ctx.accounts.authority.key(),
+            title: movie.title.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Moves a movie to another genre, taking it out of its old genre's index.
+    pub fn set_movie_genre(ctx: Context<SetMovieGenre>, genre_id: u16) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        let movie = &mut ctx.accounts.movie;
+        ctx.accounts
+            .current_genre
+            .unindex_movie(&mut ctx.accounts.current_page, movie.id)?;
+        movie.genre_id = genre_id;
+        movie.genre_page = ctx.accounts.new_genre.index_movie(
+            &mut ctx.accounts.new_page,
+            ctx.bumps.new_page,
+            movie.id,
+        )?;
+
+        emit!(MovieUpdated {
+            movie: movie.key(),
+            updated_by: ctx.accounts.authority.key(),
+            title: movie.title.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the price and window for renting a movie. A price of zero turns rentals off; premium
+    /// titles can only be watched through a rental.
+    pub fn set_rental_terms(
+        ctx: Context<SetRentalTerms>,
+        rental_price: u64,
+        rental_duration: i64,
+        is_premium: bool,
+    ) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        require!(
+            rental_price == 0 || rental_duration > 0,
+            NetMirrorError::InvalidRentalDuration
+        );
+
+        let movie = &mut ctx.accounts.movie;
+        movie.rental_price = rental_price;
+        movie.rental_duration = rental_duration;
+        movie.is_premium = is_premium;
+        Ok(())
+    }
+
+    /// `content_rating` is the minimum age tier; `allowed_regions` has one bit per licensed region.
+    pub fn set_content_restrictions(
+        ctx: Context<SetContentRestrictions>,
+        content_rating: u8,
+        allowed_regions: u64,
+    ) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        let movie = &mut ctx.accounts.movie;
+        movie.content_rating = content_rating;
+        movie.allowed_regions = allowed_regions;
+        Ok(())
+    }
+
+    /// Lets holders of `pass_mint` watch `movie`, or the whole catalog when `movie` is `None`.
+    pub fn register_access_pass(ctx: Context<RegisterAccessPass>, movie: Option<Pubkey>) -> Result<()> {
+        let access_pass = &mut ctx.accounts.access_pass;
+        access_pass.mint = ctx.accounts.pass_mint.key();
+        access_pass.movie = movie;
+        access_pass.bump = ctx.bumps.access_pass;
+        Ok(())
+    }
+
+    pub fn revoke_access_pass(_ctx: Context<RevokeAccessPass>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn delete_movie(ctx: Context<DeleteMovie>) -> Result<()> {
+        ctx.accounts.platform_state.require_uploads_enabled()?;
+
+        ctx.accounts
+            .genre
+            .unindex_movie(&mut ctx.accounts.genre_page, ctx.accounts.movie.id)?;
+
+        emit!(MovieDeleted {
+            movie: ctx.accounts.movie.key(),
+            added_by: ctx.accounts.movie.added_by,
+            deleted_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn rate_movie(ctx: Context<RateMovie>, rating: u8, body: Option<String>) -> Result<()> {
+        ctx.accounts.platform_state.require_ratings_enabled()?;
+
+        Review::validate_rating(rating)?;
+        let body = body.unwrap_or_default();
+        Review::validate_body(&body)?;
+        ctx.accounts
+            .user_account
+            .require_active_subscription(Clock::get()?.unix_timestamp)?;
+
+        let movie = &mut ctx.accounts.movie;
+        movie.add_rating(rating)?;
+
+        let review = &mut ctx.accounts.review;
+        review.reviewer = ctx.accounts.owner.key();
+        review.movie = movie.key();
+        review.rating = rating;
+        review.body = body;
+        review.is_hidden = false;
+        review.bump = ctx.bumps.review;
+
+        emit!(MovieRated {
+            movie: movie.key(),
+            reviewer: review.reviewer,
+            rating,
+            rating_sum: movie.rating_sum,
+            rating_count: movie.rating_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Changes the score and, when `body` is provided, replaces the review text.
+    pub fn update_review(ctx: Context<UpdateReview>, rating: u8, body: Option<String>) -> Result<()> {
+        ctx.accounts.platform_state.require_ratings_enabled()?;
+
+        Review::validate_rating(rating)?;
+        if let Some(body) = &body {
+            Review::validate_body(body)?;
+        }
+        ctx.accounts
+            .user_account
+            .require_active_subscription(Clock::get()?.unix_timestamp)?;
+
+        let movie = &mut ctx.accounts.movie;
+        let review = &mut ctx.accounts.review;
+        require!(!review.is_hidden, NetMirrorError::ReviewHidden);
+
+        movie.remove_rating(review.rating)?;
+        movie.add_rating(rating)?;
+        review.rating = rating;
+        if let Some(body) = body {
+            review.body = body;
+        }
+
+        emit!(MovieRated {
+            movie: movie.key(),
+            reviewer: review.reviewer,
+            rating,
+            rating_sum: movie.rating_sum,
+            rating_count: movie.rating_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Hidden reviews can't be deleted: the account is what stops their author from rating the
+    /// movie again after a takedown.
+    pub fn delete_review(ctx: Context<DeleteReview>) -> Result<()> {
+        ctx.accounts.platform_state.require_ratings_enabled()?;
+
+        let movie = &mut ctx.accounts.movie;
+        let review = &ctx.accounts.review;
+        require!(!review.is_hidden, NetMirrorError::ReviewHidden);
+        movie.remove_rating(review.rating)?;
+
+        emit!(ReviewDeleted {
+            review: review.key(),
+            movie: movie.key(),
+            reviewer: review.reviewer,
+            deleted_by: ctx.accounts.owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Hides an abusive review. Its score stops counting towards the movie's rating.
+    pub fn hide_review(ctx: Context<HideReview>) -> Result<()> {
+        let movie = &mut ctx.accounts.movie;
+        let review = &mut ctx.accounts.review;
+        require!(!review.is_hidden, NetMirrorError::ReviewHidden);
+
+        movie.remove_rating(review.rating)?;
+        review.is_hidden = true;
+
+        emit!(ReviewHidden {
+            review: review.key(),
+            movie: movie.key(),
+            reviewer: review.reviewer,
+            moderator: ctx.accounts.moderator.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Takes a review down entirely: it is hidden and its text erased. The account stays behind as
+    /// the record of the takedown, so its author can't post a new review of the movie.
+    pub fn remove_review(ctx: Context<RemoveReview>) -> Result<()> {
+        let movie = &mut ctx.accounts.movie;
+        let review = &mut ctx.accounts.review;
+        if !review.is_hidden {
+            movie.remove_rating(review.rating)?;
+            review.is_hidden = true;
+        }
+        review.body.clear();
+
+        emit!(ReviewDeleted {
+            review: review.key(),
+            movie: movie.key(),
+            reviewer: review.reviewer,
+            deleted_by: ctx.accounts.moderator.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Returns the movie's average rating scaled by 100, e.g. 425 for 4.25 stars.
+    pub fn average_rating(ctx: Context<ViewMovie>) -> Result<u64> {
+        Ok(ctx.accounts.movie.average_rating_x100())
+    }
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub paused: bool,
+    pub subscriptions_paused: bool,
+    pub uploads_paused: bool,
+    pub ratings_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryTokenAccountProposed {
+    pub treasury_token_account: Pubkey,
+    pub unlocks_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryTokenAccountChanged {
+    pub previous_account: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub payment_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenRevenueWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountCreated {
+    pub user_account: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberAdded {
+    pub user_account: Pubkey,
+    pub member: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberRemoved {
+    pub user_account: Pubkey,
+    pub member: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Subscribed {
+    pub user_account: Pubkey,
+    pub owner: Pubkey,
+    pub plan: Pubkey,
+    pub currency: PaymentCurrency,
+    pub amount: u64,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionGifted {
+    pub user_account: Pubkey,
+    pub recipient: Pubkey,
+    pub gifter: Pubkey,
+    pub plan: Pubkey,
+    pub amount: u64,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoucherCreated {
+    pub voucher: Pubkey,
+    pub purchaser: Pubkey,
+    pub plan: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoucherRedeemed {
+    pub voucher: Pubkey,
+    pub user_account: Pubkey,
+    pub owner: Pubkey,
+    pub plan: Pubkey,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrialStarted {
+    pub user_account: Pubkey,
+    pub owner: Pubkey,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub user_account: Pubkey,
+    pub owner: Pubkey,
+    pub currency: PaymentCurrency,
+    pub refund: u64,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieAdded {
+    pub movie: Pubkey,
+    pub added_by: Pubkey,
+    pub title: String,
+    pub genre_id: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieUpdated {
+    pub movie: Pubkey,
+    pub updated_by: Pubkey,
+    pub title: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieRented {
+    pub movie: Pubkey,
+    pub renter: Pubkey,
+    pub price: u64,
+    pub platform_fee: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieDeleted {
+    pub movie: Pubkey,
+    pub added_by: Pubkey,
+    pub deleted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieWatched {
+    pub movie: Pubkey,
+    pub viewer: Pubkey,
+    pub total_views: u64,
+    pub unique_viewers: u64,
+    pub counted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserAttested {
+    pub user_account: Pubkey,
+    pub attestor: Pubkey,
+    pub region: u8,
+    pub age_tier: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WatchProgressUpdated {
+    pub movie: Pubkey,
+    pub viewer: Pubkey,
+    pub position_secs: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MovieRated {
+    pub movie: Pubkey,
+    pub reviewer: Pubkey,
+    pub rating: u8,
+    pub rating_sum: u64,
+    pub rating_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochClosed {
+    pub epoch: u64,
+    pub revenue: u64,
+    pub total_views: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EpochRevenueReleased {
+    pub epoch: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorRevenueClaimed {
+    pub epoch: u64,
+    pub creator: Pubkey,
+    pub views: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewHidden {
+    pub review: Pubkey,
+    pub movie: Pubkey,
+    pub reviewer: Pubkey,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewDeleted {
+    pub review: Pubkey,
+    pub movie: Pubkey,
+    pub reviewer: Pubkey,
+    pub deleted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum NetMirrorError {
+    #[msg("User is not subscribed")]
+    UserNotSubscribed,
+    #[msg("Platform is paused")]
+    PlatformPaused,
+    #[msg("Subscriptions and payments are paused")]
+    SubscriptionsPaused,
+    #[msg("Catalog uploads and edits are paused")]
+    UploadsPaused,
+    #[msg("Ratings and reviews are paused")]
+    RatingsPaused,
+    #[msg("Signer is not the proposed admin")]
+    NotPendingAdmin,
+    #[msg("Signer is not the platform admin or a curator")]
+    Unauthorized,
+    #[msg("Curator is already on the allow-list")]
+    CuratorAlreadyAdded,
+    #[msg("Curator allow-list is full")]
+    TooManyCurators,
+    #[msg("Curator is not on the allow-list")]
+    CuratorNotFound,
+    #[msg("Plan name is too long")]
+    PlanNameTooLong,
+    #[msg("Plan duration must be positive")]
+    InvalidPlanDuration,
+    #[msg("Plan must allow at least one stream")]
+    InvalidMaxStreams,
+    #[msg("Plan member limit exceeds the household maximum")]
+    InvalidMaxMembers,
+    #[msg("Member is already part of this account")]
+    MemberAlreadyAdded,
+    #[msg("Plan member limit reached")]
+    TooManyMembers,
+    #[msg("Member not found")]
+    MemberNotFound,
+    #[msg("Signer is neither the account owner nor one of its members")]
+    NotAccountMember,
+    #[msg("Plan is not available for new subscriptions")]
+    PlanInactive,
+    #[msg("Subscription has expired")]
+    SubscriptionExpired,
+    #[msg("Subscription has already been cancelled")]
+    SubscriptionAlreadyCancelled,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Movie metadata field exceeds its maximum length")]
+    MetadataTooLong,
+    #[msg("Genre name must be non-empty, trimmed, lowercase and within the length limit")]
+    InvalidGenreName,
+    #[msg("Genre is not accepting new movies")]
+    GenreInactive,
+    #[msg("Movie is already in this genre")]
+    GenreUnchanged,
+    #[msg("Movie is not listed on this genre page")]
+    MovieNotIndexed,
+    #[msg("Epoch duration must be positive")]
+    InvalidEpochDuration,
+    #[msg("View cooldown cannot be negative")]
+    InvalidViewCooldown,
+    #[msg("Rental fee cannot exceed 100%")]
+    InvalidRentalFee,
+    #[msg("Refund grace period cannot be negative")]
+    InvalidGracePeriod,
+    #[msg("Trial duration cannot be negative")]
+    InvalidTrialDuration,
+    #[msg("Free trials are not being offered")]
+    TrialsDisabled,
+    #[msg("This account has already used its free trial")]
+    TrialAlreadyUsed,
+    #[msg("Free trials are only for accounts that have never subscribed")]
+    TrialUnavailable,
+    #[msg("Code does not match the voucher")]
+    InvalidVoucherCode,
+    #[msg("Signer is not the registered attestor")]
+    NotAttestor,
+    #[msg("Region index is out of range")]
+    InvalidRegion,
+    #[msg("This movie is restricted; the account needs an attested region and age tier")]
+    AttestationRequired,
+    #[msg("Movie is not licensed in the viewer's region")]
+    RegionNotAllowed,
+    #[msg("Viewer's age tier is below the movie's content rating")]
+    AgeRestricted,
+    #[msg("Age-rated movies can only be watched by the attested account owner")]
+    MemberAgeUnverified,
+    #[msg("Renewals must be paid in the same currency as the current term")]
+    PaymentCurrencyMismatch,
+    #[msg("Treasury cannot cover this refund")]
+    InsufficientTreasuryBalance,
+    #[msg("Withdrawal exceeds the token revenue above the refund reserve")]
+    InsufficientTokenRevenue,
+    #[msg("Rental window must be positive")]
+    InvalidRentalDuration,
+    #[msg("This movie is not available for rent")]
+    RentalUnavailable,
+    #[msg("This movie requires an active rental")]
+    RentalRequired,
+    #[msg("Access pass and token account must be passed together and match the viewer")]
+    InvalidAccessPass,
+    #[msg("Billing epoch has not ended yet")]
+    EpochStillOpen,
+    #[msg("Revenue for this epoch has already been claimed")]
+    RevenueAlreadyClaimed,
+    #[msg("Nothing to claim for this epoch")]
+    NothingToClaim,
+    #[msg("Epoch revenue has been released; claims are closed")]
+    EpochReleased,
+    #[msg("Creators can still claim from this epoch")]
+    ClaimWindowOpen,
+    #[msg("Review text is too long")]
+    ReviewTooLong,
+    #[msg("Review has been hidden by a moderator")]
+    ReviewHidden,
+    #[msg("Signer is not the platform admin or a moderator")]
+    NotModerator,
+    #[msg("Moderator is already on the allow-list")]
+    ModeratorAlreadyAdded,
+    #[msg("Moderator allow-list is full")]
+    TooManyModerators,
+    #[msg("Moderator is not on the allow-list")]
+    ModeratorNotFound,
+    #[msg("Token payments are already configured; propose a new treasury token account instead")]
+    TokenPaymentAlreadyConfigured,
+    #[msg("Token payments have not been configured")]
+    TokenPaymentNotConfigured,
+    #[msg("Plan cannot be paid for with the payment token")]
+    TokenPaymentUnavailable,
+    #[msg("Token payment requires the mint, both token accounts and the token program")]
+    MissingTokenAccounts,
+    #[msg("Mint is not the configured payment mint")]
+    InvalidPaymentMint,
+    #[msg("Token account does not belong to the subscriber")]
+    InvalidPayerTokenAccount,
+    #[msg("Token account is not the platform treasury token account")]
+    InvalidTreasuryTokenAccount,
+    #[msg("Treasury token account change is still timelocked")]
+    TreasuryChangeTimelocked,
+}
+
+#[account]
+pub struct UserAccount {
+    pub owner: Pubkey,
+    pub subscription_expiry: i64,
+    pub auto_renew: bool,
+    pub plan: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub period_start: i64,
+    pub paid_until: i64,
+    pub paid_amount: u64,
+    pub paid_currency: PaymentCurrency,
+    pub trial_used: bool,
+    pub region: u8,
+    pub age_tier: u8,
+    pub attested: bool,
+    pub bump: u8
+}
+
+impl UserAccount {
+    pub const MAX_MEMBERS: usize = 5;
+    pub const LEN: usize = 32 + 8 + 1 + 32 + (4 + 32 * Self::MAX_MEMBERS) + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 1 + 1;
+
+    pub fn has_access(&self, key: &Pubkey) -> bool {
+        self.owner == *key || self.members.contains(key)
+    }
+
+    /// What cancelling now would give back out of `paid_amount`. `period_start..paid_until` is
+    /// the paid term, so stacked renewals are prorated as one term and trial or gifted time
+    /// around it is never refunded.
+    pub fn refund_amount(&self, policy: RefundPolicy, grace_period: i64, now: i64) -> u64 {
+        let remaining = self.unused_paid_time(now);
+        if self.paid_amount == 0 || remaining == 0 {
+            return 0;
+        }
+        match policy {
+            RefundPolicy::None => 0,
+            RefundPolicy::Prorated => {
+                let term = self.paid_until - self.period_start;
+                (self.paid_amount as u128 * remaining as u128 / term as u128) as u64
+            }
+            RefundPolicy::FullWithinGrace => {
+                if now - self.period_start <= grace_period {
+                    self.paid_amount
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// How much of the paid term is still ahead of `now`.
+    pub fn unused_paid_time(&self, now: i64) -> i64 {
+        (self.paid_until - now.max(self.period_start)).max(0)
+    }
+
+    pub fn require_active_subscription(&self, now: i64) -> Result<()> {
+        require!(self.subscription_expiry != 0, NetMirrorError::UserNotSubscribed);
+        require!(self.subscription_expiry > now, NetMirrorError::SubscriptionExpired);
+        Ok(())
+    }
+
+    /// Extends access with time someone else paid for. It lands outside `period_start..paid_until`
+    /// so it can't be refunded to the account owner.
+    ///
+    /// Anyone can gift to any account, so this only ever adds time: an existing plan and its
+    /// members are kept, and `plan` is only recorded for accounts that have never had one.
+    pub fn add_unpaid_time(&mut self, plan: Pubkey, duration: i64, now: i64) -> Result<()> {
+        if self.subscription_expiry <= now {
+            self.period_start = now;
+            self.paid_until = now;
+            self.paid_amount = 0;
+        }
+        self.subscription_expiry = self
+            .subscription_expiry
+            .max(now)
+            .checked_add(duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        if self.plan == Pubkey::default() {
+            self.plan = plan;
+        }
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Movie {
+    pub id: u64,
+    #[max_len(MAX_TITLE_LEN)]
+    pub title: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
+    pub description: String,
+    #[max_len(MAX_VIDEO_URL_LEN)]
+    pub video_url: String,
+    pub genre_id: u16,
+    pub genre_page: u64,
+    pub added_by: Pubkey,
+    pub total_views: u64,
+    pub unique_viewers: u64,
+    pub rating_sum: u64,
+    pub rating_count: u64,
+    pub rental_price: u64,
+    pub rental_duration: i64,
+    pub is_premium: bool,
+    pub content_rating: u8,
+    pub allowed_regions: u64,
+    pub bump: u8 
+}
+
+impl Movie {
+    pub const REGION_COUNT: u8 = 64;
+    pub const ALL_REGIONS: u64 = u64::MAX;
+
+    const MAX_METADATA_LEN: usize =
+        MAX_TITLE_LEN + MAX_DESCRIPTION_LEN + MAX_VIDEO_URL_LEN;
+
+    /// Account size, discriminator included, needed to hold the given metadata. Oversized input is
+    /// capped at the maximum so the handler can reject it with `MetadataTooLong`.
+    pub fn space(title: &str, description: &str, video_url: &str) -> usize {
+        let metadata_len = title.len() + description.len() + video_url.len();
+        8 + Self::INIT_SPACE - Self::MAX_METADATA_LEN + metadata_len.min(Self::MAX_METADATA_LEN)
+    }
+
+    /// Account size after applying an `update_movie`. Accounts only ever grow, so rent paid when
+    /// the movie was added is never handed to whoever edits it.
+    pub fn space_after_update(
+        &self,
+        current_len: usize,
+        title: &Option<String>,
+        description: &Option<String>,
+        video_url: &Option<String>,
+    ) -> usize {
+        Self::space(
+            title.as_deref().unwrap_or(&self.title),
+            description.as_deref().unwrap_or(&self.description),
+            video_url.as_deref().unwrap_or(&self.video_url),
+        )
+        .max(current_len)
+    }
+
+    pub fn validate_title(title: &str) -> Result<()> {
+        require!(title.len() <= MAX_TITLE_LEN, NetMirrorError::MetadataTooLong);
+        Ok(())
+    }
+
+    pub fn validate_description(description: &str) -> Result<()> {
+        require!(description.len() <= MAX_DESCRIPTION_LEN, NetMirrorError::MetadataTooLong);
+        Ok(())
+    }
+
+    pub fn validate_video_url(video_url: &str) -> Result<()> {
+        require!(video_url.len() <= MAX_VIDEO_URL_LEN, NetMirrorError::MetadataTooLong);
+        Ok(())
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.content_rating > 0 || self.allowed_regions != Self::ALL_REGIONS
+    }
+
+    /// Viewers get in with an active rental or a pass for this movie; titles that aren't premium
+    /// are also open to subscribers and holders of a catalog-wide pass. `key` is the movie's
+    /// address and `pass` an access pass with the viewer's token account for its mint.
+    pub fn require_watch_access(
+        &self,
+        key: &Pubkey,
+        viewer: &Pubkey,
+        user_account: Option<&UserAccount>,
+        rental: Option<&Rental>,
+        pass: (Option<&AccessPass>, Option<&TokenAccount>),
+        now: i64,
+    ) -> Result<()> {
+        self.require_viewable_by(user_account, viewer)?;
+
+        let has_rental = rental.is_some_and(|rental| rental.expires_at > now);
+        let pass = match pass {
+            (Some(access_pass), Some(token_account)) => {
+                require!(
+                    token_account.mint == access_pass.mint && token_account.owner == *viewer,
+                    NetMirrorError::InvalidAccessPass
+                );
+                (token_account.amount > 0).then_some(access_pass.movie)
+            }
+            (None, None) => None,
+            _ => return err!(NetMirrorError::InvalidAccessPass),
+        };
+        let has_movie_pass = pass == Some(Some(*key));
+        let has_catalog_pass = pass == Some(None);
+
+        if !has_rental && !has_movie_pass {
+            require!(!self.is_premium, NetMirrorError::RentalRequired);
+            if !has_catalog_pass {
+                user_account
+                    .ok_or(NetMirrorError::UserNotSubscribed)?
+                    .require_active_subscription(now)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The attestation covers the account owner only. Regions apply to the whole household, but
+    /// an age rating can't be checked for members, so they can't watch age-rated titles.
+    pub fn require_viewable_by(&self, user_account: Option<&UserAccount>, viewer: &Pubkey) -> Result<()> {
+        if !self.is_restricted() {
+            return Ok(());
+        }
+        let user_account = user_account
+            .filter(|account| account.attested)
+            .ok_or(NetMirrorError::AttestationRequired)?;
+        require!(
+            self.allowed_regions & (1 << user_account.region) != 0,
+            NetMirrorError::RegionNotAllowed
+        );
+        if self.content_rating > 0 {
+            require!(user_account.owner == *viewer, NetMirrorError::MemberAgeUnverified);
+        }
+        require!(
+            user_account.age_tier >= self.content_rating,
+            NetMirrorError::AgeRestricted
+        );
+        Ok(())
+    }
+
+    pub fn average_rating_x100(&self) -> u64 {
+        if self.rating_count == 0 {
+            return 0;
+        }
+        self.rating_sum * 100 / self.rating_count
+    }
+
+    pub fn add_rating(&mut self, rating: u8) -> Result<()> {
+        self.rating_sum = self
+            .rating_sum
+            .checked_add(rating as u64)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        self.rating_count = self
+            .rating_count
+            .checked_add(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_rating(&mut self, rating: u8) -> Result<()> {
+        self.rating_sum = self
+            .rating_sum
+            .checked_sub(rating as u64)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        self.rating_count = self
+            .rating_count
+            .checked_sub(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Genre {
+    pub id: u16,
+    pub name: String,
+    pub movie_count: u64,
+    pub indexed_count: u64,
+    pub is_active: bool,
+    pub bump: u8
+}
+
+impl Genre {
+    pub const LEN: usize = 2 + (4 + MAX_GENRE_LEN) + 8 + 8 + 1 + 1;
+
+    /// Names are matched exactly and reserved through their `GenreName` address, so surrounding
+    /// whitespace and uppercase letters are rejected rather than normalised.
+    pub fn validate_name(name: &str) -> Result<()> {
+        require!(
+            !name.is_empty()
+                && name.len() <= MAX_GENRE_LEN
+                && name.trim() == name
+                && !name.chars().any(char::is_uppercase),
+            NetMirrorError::InvalidGenreName
+        );
+        Ok(())
+    }
+
+    /// Page that the next movie added to this genre is listed on.
+    pub fn next_page(&self) -> u64 {
+        self.indexed_count / GenrePage::MAX_MOVIES as u64
+    }
+
+    /// Appends the movie to the genre's last page and returns that page's index.
+    pub fn index_movie(&mut self, page: &mut GenrePage, page_bump: u8, movie_id: u64) -> Result<u64> {
+        let page_index = self.next_page();
+        page.genre_id = self.id;
+        page.page = page_index;
+        page.bump = page_bump;
+        page.movie_ids.push(movie_id);
+
+        self.indexed_count = self
+            .indexed_count
+            .checked_add(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        self.movie_count = self
+            .movie_count
+            .checked_add(1)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        Ok(page_index)
+    }
+
+    /// Pages are append-only, so a removed movie leaves a gap rather than shifting later pages.
+    pub fn unindex_movie(&mut self, page: &mut GenrePage, movie_id: u64) -> Result<()> {
+        let index = page
+            .movie_ids
+            .iter()
+            .position(|id| *id == movie_id)
+            .ok_or(NetMirrorError::MovieNotIndexed)?;
+        page.movie_ids.remove(index);
+        self.movie_count = self.movie_count.saturating_sub(1);
+        Ok(())
+    }
+}
+
+/// Reserves a genre name so no two genres share it.
+#[account]
+pub struct GenreName {
+    pub genre_id: u16,
+    pub bump: u8
+}
+
+impl GenreName {
+    pub const LEN: usize = 2 + 1;
+}
+
+#[account]
+pub struct GenrePage {
+    pub genre_id: u16,
+    pub page: u64,
+    pub movie_ids: Vec<u64>,
+    pub bump: u8
+}
+
+impl GenrePage {
+    pub const MAX_MOVIES: usize = 32;
+    pub const LEN: usize = 2 + 8 + (4 + 8 * Self::MAX_MOVIES) + 1;
+}
+
+#[account]
+pub struct PlatformState {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub treasury: Pubkey,
+    pub payment_mint: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub pending_treasury_token_account: Pubkey,
+    pub treasury_change_unlocks_at: i64,
+    pub attestor: Pubkey,
+    pub paused: bool,
+    pub subscriptions_paused: bool,
+    pub uploads_paused: bool,
+    pub ratings_paused: bool,
+    pub movie_count: u64,
+    pub epoch_duration: i64,
+    pub view_cooldown: i64,
+    pub rental_fee_bps: u16,
+    pub refund_policy: RefundPolicy,
+    pub refund_grace_period: i64,
+    pub trial_duration: i64,
+    pub current_epoch: u64,
+    pub reserved_revenue: u64,
+    pub refund_reserve: u64,
+    pub token_refund_reserve: u64,
+    pub curators: Vec<Pubkey>,
+    pub moderators: Vec<Pubkey>,
+    pub treasury_bump: u8,
+    pub bump: u8
+}
+
+impl PlatformState {
+    pub const MAX_CURATORS: usize = 10;
+    pub const MAX_MODERATORS: usize = 10;
+    pub const DEFAULT_VIEW_COOLDOWN: i64 = 6 * 60 * 60;
+    pub const DEFAULT_RENTAL_FEE_BPS: u16 = 2_000;
+    pub const MAX_BPS: u16 = 10_000;
+    pub const TREASURY_CHANGE_DELAY: i64 = 2 * 24 * 60 * 60;
+    pub const LEN: usize = 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 8
+        + 32
+        + 1
+        + 1
+        + 1
+        + 1
+        + 8
+        + 8
+        + 8
+        + 2
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + (4 + 32 * Self::MAX_CURATORS)
+        + (4 + 32 * Self::MAX_MODERATORS)
+        + 1
+        + 1;
+
+    /// Admin and moderation instructions ignore the pause flags so an incident can be handled
+    /// while the rest of the platform is halted.
+    pub fn require_unpaused(&self) -> Result<()> {
+        require!(!self.paused, NetMirrorError::PlatformPaused);
+        Ok(())
+    }
+
+    pub fn require_subscriptions_enabled(&self) -> Result<()> {
+        self.require_unpaused()?;
+        require!(!self.subscriptions_paused, NetMirrorError::SubscriptionsPaused);
+        Ok(())
+    }
+
+    pub fn require_uploads_enabled(&self) -> Result<()> {
+        self.require_unpaused()?;
+        require!(!self.uploads_paused, NetMirrorError::UploadsPaused);
+        Ok(())
+    }
+
+    pub fn require_ratings_enabled(&self) -> Result<()> {
+        self.require_unpaused()?;
+        require!(!self.ratings_paused, NetMirrorError::RatingsPaused);
+        Ok(())
+    }
+
+    pub fn can_manage_catalog(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.curators.contains(key)
+    }
+
+    pub fn can_moderate(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.moderators.contains(key)
+    }
+}
+
+#[account]
+pub struct RevenueEpoch {
+    pub index: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub total_views: u64,
+    pub revenue: u64,
+    pub claimed: u64,
+    pub is_closed: bool,
+    pub released: bool,
+    pub bump: u8
+}
+
+impl RevenueEpoch {
+    /// How long after an epoch ends its creators can claim before the rest can be released.
+    pub const CLAIM_WINDOW: i64 = 90 * 24 * 60 * 60;
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1;
+
+    pub fn open(&mut self, index: u64, start_time: i64, duration: i64, bump: u8) -> Result<()> {
+        self.index = index;
+        self.start_time = start_time;
+        self.end_time = start_time
+            .checked_add(duration)
+            .ok_or(NetMirrorError::ArithmeticOverflow)?;
+        self.total_views = 0;
+        self.revenue = 0;
+        self.claimed = 0;
+        self.is_closed = false;
+        self.released = false;
+        self.bump = bump;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct CreatorEpochStats {
+    pub epoch: u64,
+    pub creator: Pubkey,
+    pub views: u64,
+    pub has_claimed: bool,
+    pub bump: u8
+}
+
+impl CreatorEpochStats {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 1;
+}
+
+#[account]
+pub struct Plan {
+    pub id: u16,
+    pub name: String,
+    pub price: u64,
+    pub token_price: u64,
+    pub duration: i64,
+    pub max_streams: u8,
+    pub max_members: u8,
+    pub is_active: bool,
+    pub bump: u8
+}
+
+impl Plan {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const LEN: usize = 2 + (4 + Self::MAX_NAME_LEN) + 8 + 8 + 8 + 1 + 1 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentCurrency {
+    Sol,
+    Token,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefundPolicy {
+    None,
+    Prorated,
+    FullWithinGrace,
+}
+
+#[account]
+pub struct Voucher {
+    pub code_hash: [u8; 32],
+    pub plan: Pubkey,
+    pub duration: i64,
+    pub purchaser: Pubkey,
+    pub amount: u64,
+    pub bump: u8
+}
+
+impl Voucher {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct Rental {
+    pub renter: Pubkey,
+    pub movie: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8
+}
+
+impl Rental {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct AccessPass {
+    pub mint: Pubkey,
+    pub movie: Option<Pubkey>,
+    pub bump: u8
+}
+
+impl AccessPass {
+    pub const LEN: usize = 32 + (1 + 32) + 1;
+}
+
+#[account]
+pub struct WatchRecord {
+    pub viewer: Pubkey,
+    pub movie: Pubkey,
+    pub first_watched_at: i64,
+    pub last_watched_at: i64,
+    pub view_count: u64,
+    pub last_counted_at: i64,
+    pub resume_position_secs: u32,
+    pub bump: u8
+}
+
+impl WatchRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 4 + 1;
+}
+
+#[account]
+pub struct Review {
+    pub reviewer: Pubkey,
+    pub movie: Pubkey,
+    pub rating: u8,
+    pub body: String,
+    pub is_hidden: bool,
+    pub bump: u8
+}
+
+impl Review {
+    pub const MIN_RATING: u8 = 1;
+    pub const MAX_RATING: u8 = 5;
+    pub const MAX_BODY_LEN: usize = 500;
+    pub const LEN: usize = 32 + 32 + 1 + (4 + Self::MAX_BODY_LEN) + 1 + 1;
+
+    pub fn validate_rating(rating: u8) -> Result<()> {
+        require!(
+            (Self::MIN_RATING..=Self::MAX_RATING).contains(&rating),
+            NetMirrorError::InvalidRating
+        );
+        Ok(())
+    }
+
+    pub fn validate_body(body: &str) -> Result<()> {
+        require!(body.len() <= Self::MAX_BODY_LEN, NetMirrorError::ReviewTooLong);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PlatformState::LEN,
+        seeds = [b"state"],
+        bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RevenueEpoch::LEN,
+        seeds = [b"epoch", 0u64.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub revenue_epoch: Account<'info, RevenueEpoch>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageCurators<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageModerators<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTokenPayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        token::mint = payment_mint,
+        constraint = treasury_token_account.owner == platform_state.treasury @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.pending_admin == new_admin.key() @ NetMirrorError::NotPendingAdmin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTreasuryTokenAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        constraint = treasury_token_account.owner == platform_state.treasury @ NetMirrorError::InvalidTreasuryTokenAccount,
+        constraint = treasury_token_account.mint == platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTreasuryTokenAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(address = platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        address = platform_state.treasury_token_account @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub previous_treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = platform_state.pending_treasury_token_account @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub treasury: SystemAccount<'info>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestor<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetViewCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRentalFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRefundReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokenRevenue<'info> {
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(address = platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        address = platform_state.treasury_token_account @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub treasury: SystemAccount<'info>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetRefundPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTrialDuration<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct CreatePlan<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Plan::LEN,
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct UpdatePlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateUser<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + UserAccount::LEN,
+        seeds = [b"user", owner.key().as_ref()],
+        bump 
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageMembers<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner,
+        has_one = plan @ NetMirrorError::UserNotSubscribed
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct Subscribe<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(address = platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint)]
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ NetMirrorError::InvalidPayerTokenAccount,
+        constraint = owner_token_account.mint == platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint
+    )]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = platform_state.treasury_token_account @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16, recipient: Pubkey)]
+pub struct GiftSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", recipient.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(mut)]
+    pub gifter: Signer<'info>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32], plan_id: u16)]
+pub struct CreateVoucher<'info> {
+    #[account(
+        init,
+        payer = purchaser,
+        space = 8 + Voucher::LEN,
+        seeds = [b"voucher", code_hash.as_ref()],
+        bump
+    )]
+    pub voucher: Account<'info, Voucher>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(mut)]
+    pub purchaser: Signer<'info>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemVoucher<'info> {
+    #[account(
+        mut,
+        seeds = [b"voucher", voucher.code_hash.as_ref()],
+        bump = voucher.bump,
+        has_one = purchaser,
+        close = purchaser
+    )]
+    pub voucher: Account<'info, Voucher>,
+
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    /// CHECK: only receives the voucher's rent; checked against `voucher.purchaser`.
+    #[account(mut)]
+    pub purchaser: AccountInfo<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestUser<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", user_account.owner.as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = attestor @ NetMirrorError::NotAttestor
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub attestor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTrial<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(address = platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint)]
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ NetMirrorError::InvalidPayerTokenAccount,
+        constraint = owner_token_account.mint == platform_state.payment_mint @ NetMirrorError::InvalidPaymentMint
+    )]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = platform_state.treasury_token_account @ NetMirrorError::InvalidTreasuryTokenAccount
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(genre_id: u16, name: String)]
+pub struct CreateGenre<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Genre::LEN,
+        seeds = [b"genre", genre_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub genre: Account<'info, Genre>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GenreName::LEN,
+        seeds = [b"genre_name", name.as_bytes()],
+        bump
+    )]
+    pub genre_name: Account<'info, GenreName>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(genre_id: u16)]
+pub struct UpdateGenre<'info> {
+    #[account(
+        mut,
+        seeds = [b"genre", genre_id.to_le_bytes().as_ref()],
+        bump = genre.bump
+    )]
+    pub genre: Account<'info, Genre>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(genre_id: u16, name: String)]
+pub struct RenameGenre<'info> {
+    #[account(
+        mut,
+        seeds = [b"genre", genre_id.to_le_bytes().as_ref()],
+        bump = genre.bump
+    )]
+    pub genre: Account<'info, Genre>,
+
+    #[account(
+        mut,
+        seeds = [b"genre_name", genre.name.as_bytes()],
+        bump = genre_name.bump,
+        close = admin
+    )]
+    pub genre_name: Account<'info, GenreName>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GenreName::LEN,
+        seeds = [b"genre_name", name.as_bytes()],
+        bump
+    )]
+    pub new_genre_name: Account<'info, GenreName>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, description: String, video_url: String, genre_id: u16)]
+pub struct AddMovie<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Movie::space(&title, &description, &video_url),
+        seeds = [b"movie", platform_state.movie_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        seeds = [b"genre", genre_id.to_le_bytes().as_ref()],
+        bump = genre.bump,
+        constraint = genre.is_active @ NetMirrorError::GenreInactive
+    )]
+    pub genre: Account<'info, Genre>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GenrePage::LEN,
+        seeds = [b"genre_page", genre_id.to_le_bytes().as_ref(), genre.next_page().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub genre_page: Account<'info, GenrePage>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct WatchMovie<'info> {
+    #[account(
+        seeds = [b"user", user_account.owner.as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.has_access(&viewer.key()) @ NetMirrorError::NotAccountMember
+    )]
+    pub user_account: Option<Account<'info, UserAccount>>,
+
+    #[account(
+        seeds = [b"rental", movie.key().as_ref(), viewer.key().as_ref()],
+        bump = rental.bump
+    )]
+    pub rental: Option<Account<'info, Rental>>,
+
+    #[account(
+        seeds = [b"access_pass", access_pass.mint.as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    pub pass_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump
+    )]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", platform_state.current_epoch.to_le_bytes().as_ref()],
+        bump = revenue_epoch.bump
+    )]
+    pub revenue_epoch: Account<'info, RevenueEpoch>,
+
+    #[account(
+        init_if_needed,
+        payer = viewer,
+        space = 8 + CreatorEpochStats::LEN,
+        seeds = [b"creator_epoch", revenue_epoch.index.to_le_bytes().as_ref(), movie.added_by.as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorEpochStats>,
+
+    #[account(
+        init_if_needed,
+        payer = viewer,
+        space = 8 + WatchRecord::LEN,
+        seeds = [b"watch", movie.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub watch_record: Account<'info, WatchRecord>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RentMovie<'info> {
+    #[account(
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump,
+        has_one = added_by
+    )]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        space = 8 + Rental::LEN,
+        seeds = [b"rental", movie.key().as_ref(), renter.key().as_ref()],
+        bump
+    )]
+    pub rental: Account<'info, Rental>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    /// CHECK: only receives the uploader's share; checked against `movie.added_by`.
+    #[account(mut)]
+    pub added_by: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub renter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgress<'info> {
+    #[account(
+        seeds = [b"user", user_account.owner.as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.has_access(&viewer.key()) @ NetMirrorError::NotAccountMember
+    )]
+    pub user_account: Option<Account<'info, UserAccount>>,
+
+    #[account(
+        seeds = [b"rental", movie.key().as_ref(), viewer.key().as_ref()],
+        bump = rental.bump
+    )]
+    pub rental: Option<Account<'info, Rental>>,
+
+    #[account(
+        seeds = [b"access_pass", access_pass.mint.as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    pub pass_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump
+    )]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"watch", movie.key().as_ref(), viewer.key().as_ref()],
+        bump = watch_record.bump,
+        constraint = watch_record.viewer == viewer.key() @ NetMirrorError::Unauthorized
+    )]
+    pub watch_record: Account<'info, WatchRecord>,
 
-#[account]
-pub struct Review {
-    pub reviewer: Pubkey,
-    pub movie: Pubkey,
-    pub rating: u64,
-    pub bump: u8
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub viewer: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializePlatform<'info> {
+pub struct CloseEpoch<'info> {
     #[account(
-        init,
-        payer = admin,
-        space = 8+32+32+8+1,
+        mut,
         seeds = [b"state"],
-        bump
+        bump = platform_state.bump,
+        has_one = treasury
     )]
     pub platform_state: Account<'info, PlatformState>,
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    pub treasury: AccountInfo<'info>,
-    pub system_program: Program<'info,System>,
-}
+    #[account(
+        mut,
+        seeds = [b"epoch", platform_state.current_epoch.to_le_bytes().as_ref()],
+        bump = revenue_epoch.bump
+    )]
+    pub revenue_epoch: Account<'info, RevenueEpoch>,
 
-#[derive(Accounts)]
-pub struct CreateUser<'info> {
     #[account(
         init,
-        payer = owner,
-        space = 8+32+1+8+1,
-        seeds = [b"user", owner.key().as_ref()],
-        bump 
+        payer = payer,
+        space = 8 + RevenueEpoch::LEN,
+        seeds = [b"epoch", (platform_state.current_epoch + 1).to_le_bytes().as_ref()],
+        bump
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub next_epoch: Account<'info, RevenueEpoch>,
+
+    pub treasury: SystemAccount<'info>,
 
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(duration: i64)]
-pub struct Subscribe<'info> {
+#[instruction(epoch_index: u64)]
+pub struct ClaimCreatorRevenue<'info> {
     #[account(
         mut,
-        seeds = [b"user", owner.key().as_ref()],
-        bump
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = treasury
     )]
-    pub user_account: Account<'info, UserAccount>,
     pub platform_state: Account<'info, PlatformState>,
 
+    #[account(
+        mut,
+        seeds = [b"epoch", epoch_index.to_le_bytes().as_ref()],
+        bump = revenue_epoch.bump
+    )]
+    pub revenue_epoch: Account<'info, RevenueEpoch>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_epoch", epoch_index.to_le_bytes().as_ref(), creator.key().as_ref()],
+        bump = creator_stats.bump,
+        has_one = creator
+    )]
+    pub creator_stats: Account<'info, CreatorEpochStats>,
+
     #[account(mut)]
-    pub owner: Signer<'info>,
-    pub treasury: AccountInfo<'info>,
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String, description: String, video_url: String, genre: String)]
-pub struct AddMovie<'info> {
+#[instruction(epoch_index: u64)]
+pub struct ReleaseEpochRevenue<'info> {
     #[account(
-        init,
-        payer = admin,
-        space = 8 + 4 + 100 + 4 + 200 + 32 + 8 + 1,
-        seeds = [b"movie", admin.key().as_ref(), title.as_bytes()],
-        bump
+        mut,
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", epoch_index.to_le_bytes().as_ref()],
+        bump = revenue_epoch.bump
+    )]
+    pub revenue_epoch: Account<'info, RevenueEpoch>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    title: Option<String>,
+    description: Option<String>,
+    video_url: Option<String>
+)]
+pub struct UpdateMovie<'info> {
+    #[account(
+        mut, 
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump,
+        realloc = movie.space_after_update(
+            movie.to_account_info().data_len(),
+            &title,
+            &description,
+            &video_url
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
     )]
     pub movie: Account<'info, Movie>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>
 }
 
 #[derive(Accounts)]
-#[instruction(title: String)]
-pub struct WatchMovie<'info> {
+#[instruction(genre_id: u16)]
+pub struct SetMovieGenre<'info> {
     #[account(
         mut,
-        seeds = [b"user", owner.key().as_ref()],
-        bump = user_account.bump,
-        has_one = owner
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump,
+        constraint = movie.genre_id != genre_id @ NetMirrorError::GenreUnchanged
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"genre", movie.genre_id.to_le_bytes().as_ref()],
+        bump = current_genre.bump
+    )]
+    pub current_genre: Account<'info, Genre>,
+
+    #[account(
+        mut,
+        seeds = [b"genre_page", movie.genre_id.to_le_bytes().as_ref(), movie.genre_page.to_le_bytes().as_ref()],
+        bump = current_page.bump
+    )]
+    pub current_page: Account<'info, GenrePage>,
+
+    #[account(
+        mut,
+        seeds = [b"genre", genre_id.to_le_bytes().as_ref()],
+        bump = new_genre.bump,
+        constraint = new_genre.is_active @ NetMirrorError::GenreInactive
+    )]
+    pub new_genre: Account<'info, Genre>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GenrePage::LEN,
+        seeds = [b"genre_page", genre_id.to_le_bytes().as_ref(), new_genre.next_page().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub new_page: Account<'info, GenrePage>,
 
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRentalTerms<'info> {
     #[account(
         mut,
-        seeds = [b"movie", movie.added_by.as_ref(), title.as_bytes()],
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
         bump = movie.bump
     )]
     pub movie: Account<'info, Movie>,
 
-    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String)]
-pub struct UpdateMovie<'info> {
+pub struct SetContentRestrictions<'info> {
     #[account(
-        mut, 
-        seeds = [b"movie", added_by.key().as_ref(), title.as_bytes()],
-        bump = movie.bump,
-        has_one = added_by
+        mut,
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
+        bump = movie.bump
     )]
     pub movie: Account<'info, Movie>,
-    pub added_by: Signer<'info>
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAccessPass<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AccessPass::LEN,
+        seeds = [b"access_pass", pass_mint.key().as_ref()],
+        bump
+    )]
+    pub access_pass: Account<'info, AccessPass>,
+
+    pub pass_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAccessPass<'info> {
+    #[account(
+        mut,
+        seeds = [b"access_pass", access_pass.mint.as_ref()],
+        bump = access_pass.bump,
+        close = admin
+    )]
+    pub access_pass: Account<'info, AccessPass>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        has_one = admin
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String)]
 pub struct DeleteMovie<'info> {
     #[account(
         mut, 
-        seeds = [b"movie", added_by.key().as_ref(), title.as_bytes()],
+        seeds = [b"movie", movie.id.to_le_bytes().as_ref()],
         bump = movie.bump,
+        has_one = added_by,
         close = added_by
     )]
     pub movie: Account<'info, Movie>,
-    pub added_by: Signer<'info>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_manage_catalog(&authority.key()) @ NetMirrorError::Unauthorized
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        seeds = [b"genre", movie.genre_id.to_le_bytes().as_ref()],
+        bump = genre.bump
+    )]
+    pub genre: Account<'info, Genre>,
+
+    #[account(
+        mut,
+        seeds = [b"genre_page", movie.genre_id.to_le_bytes().as_ref(), movie.genre_page.to_le_bytes().as_ref()],
+        bump = genre_page.bump
+    )]
+    pub genre_page: Account<'info, GenrePage>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: receives the movie's rent; must match `movie.added_by`.
+    #[account(mut)]
+    pub added_by: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -239,7 +3502,7 @@ pub struct RateMovie<'info> {
 
     #[account(
         seeds = [b"user", owner.key().as_ref()],
-        bump
+        bump = user_account.bump
     )]
 
     pub user_account: Account<'info, UserAccount>,
@@ -247,13 +3510,118 @@ pub struct RateMovie<'info> {
     #[account(
         init, 
         payer = owner,
-        space = 8 + 32 + 32 + 1 + 1,
+        space = 8 + Review::LEN,
         seeds = [b"review", movie.key().as_ref(), owner.key().as_ref()],
         bump
     )]
     pub review: Account<'info, Review>,
 
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct UpdateReview<'info> {
+    #[account(mut)]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        seeds = [b"user", owner.key().as_ref()],
+        bump = user_account.bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie.key().as_ref(), owner.key().as_ref()],
+        bump = review.bump
+    )]
+    pub review: Account<'info, Review>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteReview<'info> {
+    #[account(mut)]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie.key().as_ref(), owner.key().as_ref()],
+        bump = review.bump,
+        close = owner
+    )]
+    pub review: Account<'info, Review>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HideReview<'info> {
+    #[account(mut)]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie.key().as_ref(), review.reviewer.as_ref()],
+        bump = review.bump
+    )]
+    pub review: Account<'info, Review>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_moderate(&moderator.key()) @ NetMirrorError::NotModerator
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReview<'info> {
+    #[account(mut)]
+    pub movie: Account<'info, Movie>,
+
+    #[account(
+        mut,
+        seeds = [b"review", movie.key().as_ref(), review.reviewer.as_ref()],
+        bump = review.bump
+    )]
+    pub review: Account<'info, Review>,
+
+    #[account(
+        seeds = [b"state"],
+        bump = platform_state.bump,
+        constraint = platform_state.can_moderate(&moderator.key()) @ NetMirrorError::NotModerator
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub moderator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewMovie<'info> {
+    pub movie: Account<'info, Movie>,
 }
\ No newline at end of file
@@ -0,0 +1,288 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, review_address, Platform, PLAN_ID, TOKEN_PRICE};
+use netmirror::{
+    accounts, instruction, AdminChanged, NetMirrorError, PauseFlagsUpdated, PaymentCurrency,
+    PlatformState, TreasuryTokenAccountChanged, TreasuryTokenAccountProposed,
+};
+
+#[derive(Default)]
+struct PauseFlags {
+    paused: bool,
+    subscriptions_paused: bool,
+    uploads_paused: bool,
+    ratings_paused: bool,
+}
+
+fn set_pause_flags(platform: &mut Platform, admin: Pubkey, flags: PauseFlags) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::SetPauseFlags {
+            platform_state: platform.platform_state,
+            admin,
+        },
+        instruction::SetPauseFlags {
+            paused: flags.paused,
+            subscriptions_paused: flags.subscriptions_paused,
+            uploads_paused: flags.uploads_paused,
+            ratings_paused: flags.ratings_paused,
+        },
+        &[&admin],
+    )
+}
+
+fn propose_admin(platform: &mut Platform, admin: Pubkey, new_admin: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::ProposeAdmin {
+            platform_state: platform.platform_state,
+            admin,
+        },
+        instruction::ProposeAdmin { new_admin },
+        &[&admin],
+    )
+}
+
+fn accept_admin(platform: &mut Platform, new_admin: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::AcceptAdmin {
+            platform_state: platform.platform_state,
+            new_admin,
+        },
+        instruction::AcceptAdmin {},
+        &[&new_admin],
+    )
+}
+
+fn propose_treasury_token_account(
+    platform: &mut Platform,
+    treasury_token_account: Pubkey,
+) -> Result<(), ProgramError> {
+    let admin = platform.admin;
+    platform.bank.send(
+        accounts::ProposeTreasuryTokenAccount {
+            platform_state: platform.platform_state,
+            treasury_token_account,
+            admin,
+        },
+        instruction::ProposeTreasuryTokenAccount {},
+        &[&admin],
+    )
+}
+
+fn accept_treasury_token_account(platform: &mut Platform) -> Result<(), ProgramError> {
+    let admin = platform.admin;
+    let state = platform.state();
+    platform.bank.send(
+        accounts::AcceptTreasuryTokenAccount {
+            platform_state: platform.platform_state,
+            payment_mint: state.payment_mint,
+            previous_treasury_token_account: state.treasury_token_account,
+            treasury_token_account: state.pending_treasury_token_account,
+            treasury: platform.treasury,
+            admin,
+            token_program: anchor_spl::token::ID,
+        },
+        instruction::AcceptTreasuryTokenAccount {},
+        &[&admin],
+    )
+}
+
+#[test]
+fn the_global_pause_halts_user_instructions() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+    let admin = platform.admin;
+    let flags = PauseFlags { paused: true, ..Default::default() };
+    set_pause_flags(&mut platform, admin, flags).unwrap();
+
+    let events = platform.bank.events::<PauseFlagsUpdated>();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].paused);
+    assert!(platform.state().paused);
+
+    let result = platform.subscribe(owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::PlatformPaused)));
+    let result = platform.add_movie(admin, "Metropolis");
+    assert_eq!(result.map(|_| ()), Err(anchor_error(NetMirrorError::PlatformPaused)));
+
+    set_pause_flags(&mut platform, admin, PauseFlags::default()).unwrap();
+    platform.subscribe(owner, user_account).unwrap();
+}
+
+#[test]
+fn each_feature_flag_only_pauses_its_own_feature() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    let flags = PauseFlags { subscriptions_paused: true, ..Default::default() };
+
+    set_pause_flags(&mut platform, admin, flags).unwrap();
+    let result = platform.subscribe(owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::SubscriptionsPaused)));
+    platform.add_movie(admin, "Nosferatu").unwrap();
+
+    let flags = PauseFlags { uploads_paused: true, ..Default::default() };
+
+    set_pause_flags(&mut platform, admin, flags).unwrap();
+    let result = platform.add_movie(admin, "Sunrise");
+    assert_eq!(result.map(|_| ()), Err(anchor_error(NetMirrorError::UploadsPaused)));
+    platform.subscribe(owner, user_account).unwrap();
+
+    let flags = PauseFlags { ratings_paused: true, ..Default::default() };
+
+    set_pause_flags(&mut platform, admin, flags).unwrap();
+    let result = platform.bank.send(
+        accounts::RateMovie {
+            movie,
+            user_account,
+            review: review_address(&movie, &owner),
+            platform_state: platform.platform_state,
+            owner,
+            system_program: system_program::ID,
+        },
+        instruction::RateMovie {
+            rating: 5,
+            body: None,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RatingsPaused)));
+    platform.watch(owner, Some(user_account), movie).unwrap();
+}
+
+#[test]
+fn set_pause_flags_is_admin_only() {
+    let mut platform = Platform::new();
+    let intruder = platform.bank.wallet(1);
+
+    let flags = PauseFlags { paused: true, ..Default::default() };
+
+    let result = set_pause_flags(&mut platform, intruder, flags);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+    assert!(!platform.state().paused);
+}
+
+#[test]
+fn admin_handover_takes_effect_once_the_new_admin_accepts() {
+    let mut platform = Platform::new();
+    let previous_admin = platform.admin;
+    let new_admin = platform.bank.wallet(1);
+
+    propose_admin(&mut platform, previous_admin, new_admin).unwrap();
+    let state: PlatformState = platform.state();
+    assert_eq!(state.admin, previous_admin);
+    assert_eq!(state.pending_admin, new_admin);
+
+    accept_admin(&mut platform, new_admin).unwrap();
+    let state: PlatformState = platform.state();
+    assert_eq!(state.admin, new_admin);
+    assert_eq!(state.pending_admin, Pubkey::default());
+
+    let events = platform.bank.events::<AdminChanged>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].previous_admin, previous_admin);
+    assert_eq!(events[0].admin, new_admin);
+
+    let result = set_pause_flags(&mut platform, previous_admin, PauseFlags::default());
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+    set_pause_flags(&mut platform, new_admin, PauseFlags::default()).unwrap();
+}
+
+#[test]
+fn only_the_proposed_admin_can_accept() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let new_admin = platform.bank.wallet(1);
+    let intruder = platform.bank.wallet(1);
+
+    let result = accept_admin(&mut platform, intruder);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotPendingAdmin)));
+
+    propose_admin(&mut platform, admin, new_admin).unwrap();
+    let result = accept_admin(&mut platform, intruder);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotPendingAdmin)));
+
+    let result = propose_admin(&mut platform, intruder, intruder);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+
+    // Proposing the default key withdraws the proposal.
+    propose_admin(&mut platform, admin, Pubkey::default()).unwrap();
+    let result = accept_admin(&mut platform, new_admin);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotPendingAdmin)));
+    assert_eq!(platform.state().admin, admin);
+}
+
+#[test]
+fn treasury_token_account_rotation_waits_out_the_timelock_and_moves_the_balance() {
+    let mut platform = Platform::new();
+    let (mint, previous_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let owner_token_account = platform.bank.create_token_account(&mint, &owner, TOKEN_PRICE);
+    let token_accounts = (mint, owner_token_account, previous_account);
+    platform
+        .bank
+        .send(
+            platform.subscribe_accounts(owner, user_account, Some(token_accounts)),
+            instruction::Subscribe {
+                _plan_id: PLAN_ID,
+                currency: PaymentCurrency::Token,
+            },
+            &[&owner],
+        )
+        .unwrap();
+    let treasury = platform.treasury;
+    let next_account = platform.bank.create_token_account(&mint, &treasury, 0);
+
+    propose_treasury_token_account(&mut platform, next_account).unwrap();
+    let events = platform.bank.events::<TreasuryTokenAccountProposed>();
+    assert_eq!(events[0].treasury_token_account, next_account);
+    assert_eq!(events[0].unlocks_at, platform.bank.now() + PlatformState::TREASURY_CHANGE_DELAY);
+
+    let result = accept_treasury_token_account(&mut platform);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TreasuryChangeTimelocked)));
+
+    platform.bank.warp(PlatformState::TREASURY_CHANGE_DELAY);
+    accept_treasury_token_account(&mut platform).unwrap();
+
+    let state = platform.state();
+    assert_eq!(state.treasury_token_account, next_account);
+    assert_eq!(state.payment_mint, mint);
+    assert_eq!(state.pending_treasury_token_account, Pubkey::default());
+    assert_eq!(platform.bank.token_balance(&previous_account), 0);
+    assert_eq!(platform.bank.token_balance(&next_account), TOKEN_PRICE);
+
+    let events = platform.bank.events::<TreasuryTokenAccountChanged>();
+    assert_eq!(events[0].previous_account, previous_account);
+    assert_eq!(events[0].treasury_token_account, next_account);
+}
+
+#[test]
+fn treasury_token_account_rotation_keeps_the_payment_mint() {
+    let mut platform = Platform::new();
+    platform.configure_token_payment();
+    let admin = platform.admin;
+    let treasury = platform.treasury;
+    let other_mint = platform.bank.create_mint(&admin, 9);
+    let other_account = platform.bank.create_token_account(&other_mint, &treasury, 0);
+
+    let result = propose_treasury_token_account(&mut platform, other_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidPaymentMint)));
+}
+
+#[test]
+fn treasury_token_accounts_must_be_held_by_the_treasury() {
+    let mut platform = Platform::new();
+    let (mint, _) = platform.configure_token_payment();
+    let admin = platform.admin;
+    let admin_account = platform.bank.create_token_account(&mint, &admin, 0);
+
+    let result = propose_treasury_token_account(&mut platform, admin_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidTreasuryTokenAccount)));
+    assert_eq!(platform.state().pending_treasury_token_account, Pubkey::default());
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{
+    anchor_error, genre_address, genre_name_address, genre_page_address, movie_address, Platform, GENRE_ID,
+};
+use netmirror::{
+    accounts, instruction, Genre, GenreName, GenrePage, Movie, MovieAdded, MovieDeleted, MovieUpdated,
+    NetMirrorError, PlatformState, MAX_TITLE_LEN,
+};
+
+fn update_movie(
+    platform: &mut Platform,
+    movie: Pubkey,
+    authority: Pubkey,
+    args: instruction::UpdateMovie,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::UpdateMovie {
+            movie,
+            platform_state: platform.platform_state,
+            authority,
+            system_program: system_program::ID,
+        },
+        args,
+        &[&authority],
+    )
+}
+
+fn delete_accounts(platform: &Platform, movie: Pubkey, authority: Pubkey) -> accounts::DeleteMovie {
+    let state: Movie = platform.bank.get(&movie);
+    accounts::DeleteMovie {
+        movie,
+        platform_state: platform.platform_state,
+        genre: platform.genre,
+        genre_page: genre_page_address(state.genre_id, state.genre_page),
+        authority,
+        added_by: state.added_by,
+    }
+}
+
+fn deactivate_genre(platform: &mut Platform, genre_id: u16) {
+    let admin = platform.admin;
+    platform
+        .bank
+        .send(
+            accounts::UpdateGenre {
+                genre: genre_address(genre_id),
+                platform_state: platform.platform_state,
+                admin,
+            },
+            instruction::UpdateGenre {
+                _genre_id: genre_id,
+                is_active: false,
+            },
+            &[&admin],
+        )
+        .unwrap();
+}
+
+fn rename_genre(platform: &mut Platform, genre_id: u16, name: &str) -> Result<(), ProgramError> {
+    let admin = platform.admin;
+    let genre: Genre = platform.bank.get(&genre_address(genre_id));
+    platform.bank.send(
+        accounts::RenameGenre {
+            genre: genre_address(genre_id),
+            genre_name: genre_name_address(&genre.name),
+            new_genre_name: genre_name_address(name),
+            platform_state: platform.platform_state,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::RenameGenre {
+            _genre_id: genre_id,
+            name: name.to_string(),
+        },
+        &[&admin],
+    )
+}
+
+fn set_movie_genre(
+    platform: &mut Platform,
+    movie: Pubkey,
+    authority: Pubkey,
+    genre_id: u16,
+) -> Result<(), ProgramError> {
+    let state: Movie = platform.bank.get(&movie);
+    let new_genre: Genre = platform.bank.get(&genre_address(genre_id));
+    platform.bank.send(
+        accounts::SetMovieGenre {
+            movie,
+            current_genre: genre_address(state.genre_id),
+            current_page: genre_page_address(state.genre_id, state.genre_page),
+            new_genre: genre_address(genre_id),
+            new_page: genre_page_address(genre_id, new_genre.next_page()),
+            platform_state: platform.platform_state,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetMovieGenre { genre_id },
+        &[&authority],
+    )
+}
+
+#[test]
+fn add_movie_stores_metadata_and_indexes_it_under_its_genre() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+
+    assert_eq!(movie, movie_address(0));
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.id, 0);
+    assert_eq!(state.title, "Metropolis");
+    assert_eq!(state.added_by, admin);
+    assert_eq!(state.genre_id, GENRE_ID);
+    assert_eq!(state.total_views, 0);
+    assert_eq!(platform.bank.get::<PlatformState>(&platform.platform_state).movie_count, 1);
+
+    let genre: Genre = platform.bank.get(&platform.genre);
+    assert_eq!(genre.movie_count, 1);
+    let page: GenrePage = platform.bank.get(&genre_page_address(GENRE_ID, state.genre_page));
+    assert_eq!(page.movie_ids, vec![0]);
+
+    let events = platform.bank.events::<MovieAdded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].movie, movie);
+    assert_eq!(events[0].genre_id, GENRE_ID);
+}
+
+#[test]
+fn add_movie_sizes_the_account_to_its_metadata() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let short = platform.add_movie(admin, "M").unwrap();
+    let long = platform.add_movie(admin, &"M".repeat(MAX_TITLE_LEN)).unwrap();
+
+    let short_len = platform.bank.account(&short).unwrap().data.len();
+    let long_len = platform.bank.account(&long).unwrap().data.len();
+    assert_eq!(long_len - short_len, MAX_TITLE_LEN - 1);
+}
+
+#[test]
+fn curators_can_add_movies() {
+    let mut platform = Platform::new();
+    let curator = platform.add_curator();
+    let movie = platform.add_movie(curator, "Nosferatu").unwrap();
+
+    assert_eq!(platform.bank.get::<Movie>(&movie).added_by, curator);
+}
+
+#[test]
+fn add_movie_rejects_callers_outside_the_catalog_team() {
+    let mut platform = Platform::new();
+    let outsider = platform.bank.wallet(5);
+
+    let result = platform.add_movie(outsider, "Bootleg");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::Unauthorized)));
+    assert_eq!(platform.bank.get::<PlatformState>(&platform.platform_state).movie_count, 0);
+}
+
+#[test]
+fn add_movie_rejects_oversized_title() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+
+    let result = platform.add_movie(admin, &"M".repeat(MAX_TITLE_LEN + 1));
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MetadataTooLong)));
+}
+
+#[test]
+fn add_movie_rejects_an_address_other_than_the_next_id() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let mut accounts = platform.add_movie_accounts(admin);
+    accounts.movie = movie_address(1);
+
+    let result = platform.bank.send(
+        accounts,
+        instruction::AddMovie {
+            title: "Out of order".to_string(),
+            description: String::new(),
+            video_url: String::new(),
+            genre_id: GENRE_ID,
+        },
+        &[&admin],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn add_movie_rejects_inactive_genre() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    deactivate_genre(&mut platform, GENRE_ID);
+
+    let result = platform.add_movie(admin, "Metropolis");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::GenreInactive)));
+}
+
+#[test]
+fn update_movie_changes_only_the_given_fields_and_grows_the_account() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "M").unwrap();
+    let len_before = platform.bank.account(&movie).unwrap().data.len();
+
+    update_movie(
+        &mut platform,
+        movie,
+        admin,
+        instruction::UpdateMovie {
+            title: Some("Metropolis (Restored)".to_string()),
+            description: None,
+            video_url: None,
+        },
+    )
+    .unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.title, "Metropolis (Restored)");
+    assert_eq!(state.description, "A film");
+    assert_eq!(
+        platform.bank.account(&movie).unwrap().data.len(),
+        len_before + "Metropolis (Restored)".len() - 1
+    );
+
+    let events = platform.bank.events::<MovieUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].updated_by, admin);
+}
+
+#[test]
+fn update_movie_never_shrinks_the_account() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let len_before = platform.bank.account(&movie).unwrap().data.len();
+
+    update_movie(
+        &mut platform,
+        movie,
+        admin,
+        instruction::UpdateMovie {
+            title: Some("M".to_string()),
+            description: None,
+            video_url: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(platform.bank.get::<Movie>(&movie).title, "M");
+    assert_eq!(platform.bank.account(&movie).unwrap().data.len(), len_before);
+}
+
+#[test]
+fn update_movie_rejects_callers_outside_the_catalog_team() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let outsider = platform.bank.wallet(1);
+
+    let result = update_movie(
+        &mut platform,
+        movie,
+        outsider,
+        instruction::UpdateMovie {
+            title: Some("Defaced".to_string()),
+            description: None,
+            video_url: None,
+        },
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::Unauthorized)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).title, "Metropolis");
+}
+
+#[test]
+fn delete_movie_returns_rent_to_the_uploader_and_unindexes_it() {
+    let mut platform = Platform::new();
+    let curator = platform.add_curator();
+    let movie = platform.add_movie(curator, "Nosferatu").unwrap();
+    let rent = platform.bank.lamports(&movie);
+    let curator_before = platform.bank.lamports(&curator);
+    let admin = platform.admin;
+    let page = genre_page_address(GENRE_ID, 0);
+
+    let accounts = delete_accounts(&platform, movie, admin);
+    platform.bank.send(accounts, instruction::DeleteMovie {}, &[&admin]).unwrap();
+
+    assert!(platform.bank.account(&movie).is_none());
+    assert_eq!(platform.bank.lamports(&curator), curator_before + rent);
+    assert!(platform.bank.get::<GenrePage>(&page).movie_ids.is_empty());
+    assert_eq!(platform.bank.get::<Genre>(&platform.genre).movie_count, 0);
+
+    let events = platform.bank.events::<MovieDeleted>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].added_by, curator);
+    assert_eq!(events[0].deleted_by, admin);
+}
+
+#[test]
+fn delete_movie_rejects_callers_outside_the_catalog_team() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let outsider = platform.bank.wallet(1);
+
+    let accounts = delete_accounts(&platform, movie, outsider);
+    let result = platform.bank.send(accounts, instruction::DeleteMovie {}, &[&outsider]);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::Unauthorized)));
+    assert!(platform.bank.account(&movie).is_some());
+}
+
+#[test]
+fn delete_movie_refuses_to_send_rent_anywhere_but_the_uploader() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let thief = platform.bank.wallet(1);
+
+    let mut accounts = delete_accounts(&platform, movie, admin);
+    accounts.added_by = thief;
+    let result = platform.bank.send(accounts, instruction::DeleteMovie {}, &[&admin]);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+}
+
+#[test]
+fn create_genre_rejects_names_that_are_not_lowercase() {
+    let mut platform = Platform::new();
+
+    let result = platform.create_genre(2, "Comedy");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidGenreName)));
+    let result = platform.create_genre(2, " comedy");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidGenreName)));
+    assert!(platform.bank.account(&genre_address(2)).is_none());
+}
+
+#[test]
+fn create_genre_reserves_its_name() {
+    let mut platform = Platform::new();
+
+    let reservation: GenreName = platform.bank.get(&genre_name_address("drama"));
+    assert_eq!(reservation.genre_id, GENRE_ID);
+
+    let result = platform.create_genre(2, "drama");
+    assert!(result.is_err());
+    assert!(platform.bank.account(&genre_address(2)).is_none());
+}
+
+#[test]
+fn rename_genre_frees_the_old_name_and_reserves_the_new_one() {
+    let mut platform = Platform::new();
+    platform.create_genre(2, "comedy").unwrap();
+
+    let result = rename_genre(&mut platform, GENRE_ID, "comedy");
+    assert!(result.is_err());
+    let result = rename_genre(&mut platform, GENRE_ID, "Film Noir");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidGenreName)));
+
+    rename_genre(&mut platform, GENRE_ID, "film noir").unwrap();
+    assert_eq!(platform.bank.get::<Genre>(&platform.genre).name, "film noir");
+    assert!(platform.bank.account(&genre_name_address("drama")).is_none());
+    assert_eq!(platform.bank.get::<GenreName>(&genre_name_address("film noir")).genre_id, GENRE_ID);
+
+    platform.create_genre(3, "drama").unwrap();
+    assert!(platform.create_genre(4, "film noir").is_err());
+}
+
+#[test]
+fn set_movie_genre_moves_the_movie_to_the_new_genres_page() {
+    let mut platform = Platform::new();
+    platform.create_genre(2, "comedy").unwrap();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Sherlock Jr.").unwrap();
+
+    let result = set_movie_genre(&mut platform, movie, admin, GENRE_ID);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::GenreUnchanged)));
+
+    set_movie_genre(&mut platform, movie, admin, 2).unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.genre_id, 2);
+    assert_eq!(state.genre_page, 0);
+    assert!(platform.bank.get::<GenrePage>(&genre_page_address(GENRE_ID, 0)).movie_ids.is_empty());
+    assert_eq!(platform.bank.get::<GenrePage>(&genre_page_address(2, 0)).movie_ids, vec![0]);
+    assert_eq!(platform.bank.get::<Genre>(&platform.genre).movie_count, 0);
+    assert_eq!(platform.bank.get::<Genre>(&genre_address(2)).movie_count, 1);
+
+    let events = platform.bank.events::<MovieUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].movie, movie);
+}
+
+#[test]
+fn set_movie_genre_rejects_an_inactive_genre() {
+    let mut platform = Platform::new();
+    platform.create_genre(2, "comedy").unwrap();
+    deactivate_genre(&mut platform, 2);
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Sherlock Jr.").unwrap();
+
+    let result = set_movie_genre(&mut platform, movie, admin, 2);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::GenreInactive)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).genre_id, GENRE_ID);
+}
+
+#[test]
+fn set_movie_genre_rejects_callers_outside_the_catalog_team() {
+    let mut platform = Platform::new();
+    platform.create_genre(2, "comedy").unwrap();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Sherlock Jr.").unwrap();
+    let outsider = platform.bank.wallet(1);
+
+    let result = set_movie_genre(&mut platform, movie, outsider, 2);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::Unauthorized)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).genre_id, GENRE_ID);
+}
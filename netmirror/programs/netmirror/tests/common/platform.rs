@@ -0,0 +1,419 @@
+//! A platform initialised the way the integration tests expect: one SOL/token plan, one genre
+//! and the admin as the only catalog manager.
+
+use anchor_lang::{
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use netmirror::{accounts, instruction, Genre, Movie, PaymentCurrency, PlatformState, RefundPolicy, UserAccount};
+
+use super::{pda, Bank, LAMPORTS_PER_SOL};
+
+pub const PLAN_ID: u16 = 1;
+pub const PLAN_DURATION: i64 = 30 * 24 * 60 * 60;
+pub const PLAN_MAX_MEMBERS: u8 = 2;
+pub const SOL_PRICE: u64 = LAMPORTS_PER_SOL / 10;
+pub const TOKEN_PRICE: u64 = 4_990_000;
+pub const EPOCH_DURATION: i64 = 7 * 24 * 60 * 60;
+pub const GENRE_ID: u16 = 1;
+
+pub struct Platform {
+    pub bank: Bank,
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub platform_state: Pubkey,
+    pub plan: Pubkey,
+    pub genre: Pubkey,
+}
+
+impl Platform {
+    pub fn new() -> Self {
+        let mut bank = Bank::new();
+        let admin = bank.wallet(10);
+        let treasury = pda(&[b"treasury"]);
+        let platform_state = pda(&[b"state"]);
+        let plan = pda(&[b"plan", &PLAN_ID.to_le_bytes()]);
+        let genre = genre_address(GENRE_ID);
+
+        bank.send(
+            accounts::InitializePlatform {
+                platform_state,
+                revenue_epoch: pda(&[b"epoch", &0u64.to_le_bytes()]),
+                admin,
+                treasury,
+                system_program: system_program::ID,
+            },
+            instruction::InitializePlatform {
+                epoch_duration: EPOCH_DURATION,
+            },
+            &[&admin],
+        )
+        .unwrap();
+        bank.send(
+            accounts::CreatePlan {
+                plan,
+                platform_state,
+                admin,
+                system_program: system_program::ID,
+            },
+            instruction::CreatePlan {
+                plan_id: PLAN_ID,
+                name: "Standard Monthly".to_string(),
+                price: SOL_PRICE,
+                token_price: TOKEN_PRICE,
+                duration: PLAN_DURATION,
+                max_streams: 2,
+                max_members: PLAN_MAX_MEMBERS,
+            },
+            &[&admin],
+        )
+        .unwrap();
+
+        let mut platform = Self {
+            bank,
+            admin,
+            treasury,
+            platform_state,
+            plan,
+            genre,
+        };
+        platform.create_genre(GENRE_ID, "drama").unwrap();
+        platform
+    }
+
+    pub fn create_genre(&mut self, genre_id: u16, name: &str) -> Result<(), ProgramError> {
+        let admin = self.admin;
+        self.bank.send(
+            accounts::CreateGenre {
+                genre: genre_address(genre_id),
+                genre_name: genre_name_address(name),
+                platform_state: self.platform_state,
+                admin,
+                system_program: system_program::ID,
+            },
+            instruction::CreateGenre {
+                genre_id,
+                name: name.to_string(),
+            },
+            &[&admin],
+        )
+    }
+
+    pub fn state(&self) -> PlatformState {
+        self.bank.get(&self.platform_state)
+    }
+
+    pub fn create_user(&mut self) -> (Pubkey, Pubkey) {
+        let owner = self.bank.wallet(1);
+        let user_account = pda(&[b"user", owner.as_ref()]);
+        self.bank
+            .send(
+                accounts::CreateUser {
+                    user_account,
+                    platform_state: self.platform_state,
+                    owner,
+                    system_program: system_program::ID,
+                },
+                instruction::CreateAccount {},
+                &[&owner],
+            )
+            .unwrap();
+        (owner, user_account)
+    }
+
+    pub fn subscribe_accounts(
+        &self,
+        owner: Pubkey,
+        user_account: Pubkey,
+        token_accounts: Option<(Pubkey, Pubkey, Pubkey)>,
+    ) -> accounts::Subscribe {
+        accounts::Subscribe {
+            user_account,
+            platform_state: self.platform_state,
+            plan: self.plan,
+            owner,
+            treasury: self.treasury,
+            payment_mint: token_accounts.map(|(mint, _, _)| mint),
+            owner_token_account: token_accounts.map(|(_, from, _)| from),
+            treasury_token_account: token_accounts.map(|(_, _, to)| to),
+            token_program: token_accounts.map(|_| anchor_spl::token::ID),
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn subscribe(&mut self, owner: Pubkey, user_account: Pubkey) -> Result<(), ProgramError> {
+        let accounts = self.subscribe_accounts(owner, user_account, None);
+        self.bank.send(
+            accounts,
+            instruction::Subscribe {
+                _plan_id: PLAN_ID,
+                currency: PaymentCurrency::Sol,
+            },
+            &[&owner],
+        )
+    }
+
+    pub fn subscribed_user(&mut self) -> (Pubkey, Pubkey) {
+        let (owner, user_account) = self.create_user();
+        self.subscribe(owner, user_account).unwrap();
+        (owner, user_account)
+    }
+
+    pub fn set_refund_policy(&mut self, refund_policy: RefundPolicy, refund_grace_period: i64) {
+        self.bank
+            .send(
+                accounts::SetRefundPolicy {
+                    platform_state: self.platform_state,
+                    admin: self.admin,
+                },
+                instruction::SetRefundPolicy {
+                    refund_policy,
+                    refund_grace_period,
+                },
+                &[&self.admin],
+            )
+            .unwrap();
+    }
+
+    /// Sets the global pause flag and clears the per-feature ones.
+    pub fn pause(&mut self) {
+        self.bank
+            .send(
+                accounts::SetPauseFlags {
+                    platform_state: self.platform_state,
+                    admin: self.admin,
+                },
+                instruction::SetPauseFlags {
+                    paused: true,
+                    subscriptions_paused: false,
+                    uploads_paused: false,
+                    ratings_paused: false,
+                },
+                &[&self.admin],
+            )
+            .unwrap();
+    }
+
+    /// `token_accounts` is `(mint, owner token account, treasury token account)`, as for
+    /// `subscribe_accounts`.
+    pub fn cancel_accounts(
+        &self,
+        owner: Pubkey,
+        user_account: Pubkey,
+        token_accounts: Option<(Pubkey, Pubkey, Pubkey)>,
+    ) -> accounts::CancelSubscription {
+        accounts::CancelSubscription {
+            user_account,
+            platform_state: self.platform_state,
+            owner,
+            treasury: self.treasury,
+            payment_mint: token_accounts.map(|(mint, _, _)| mint),
+            owner_token_account: token_accounts.map(|(_, to, _)| to),
+            treasury_token_account: token_accounts.map(|(_, _, from)| from),
+            token_program: token_accounts.map(|_| anchor_spl::token::ID),
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn cancel(&mut self, owner: Pubkey, user_account: Pubkey) -> Result<(), ProgramError> {
+        let accounts = self.cancel_accounts(owner, user_account, None);
+        self.bank.send(accounts, instruction::CancelSubscription {}, &[&owner])
+    }
+
+    /// An extra plan next to the standard one, priced in SOL only.
+    pub fn create_plan(&mut self, plan_id: u16, price: u64, max_members: u8) -> Pubkey {
+        let plan = pda(&[b"plan", &plan_id.to_le_bytes()]);
+        self.bank
+            .send(
+                accounts::CreatePlan {
+                    plan,
+                    platform_state: self.platform_state,
+                    admin: self.admin,
+                    system_program: system_program::ID,
+                },
+                instruction::CreatePlan {
+                    plan_id,
+                    name: format!("Plan {plan_id}"),
+                    price,
+                    token_price: 0,
+                    duration: PLAN_DURATION,
+                    max_streams: 1,
+                    max_members,
+                },
+                &[&self.admin],
+            )
+            .unwrap();
+        plan
+    }
+
+    pub fn add_member(&mut self, owner: Pubkey, user_account: Pubkey, member: Pubkey) -> Result<(), ProgramError> {
+        let plan = self.bank.get::<UserAccount>(&user_account).plan;
+        self.bank.send(
+            accounts::ManageMembers {
+                user_account,
+                plan,
+                platform_state: self.platform_state,
+                owner,
+            },
+            instruction::AddMember { member },
+            &[&owner],
+        )
+    }
+
+    pub fn configure_token_payment(&mut self) -> (Pubkey, Pubkey) {
+        let mint = self.bank.create_mint(&self.admin, 6);
+        let treasury_token_account = self.bank.create_token_account(&mint, &self.treasury, 0);
+        self.bank
+            .send(
+                accounts::ConfigureTokenPayment {
+                    platform_state: self.platform_state,
+                    payment_mint: mint,
+                    treasury_token_account,
+                    admin: self.admin,
+                },
+                instruction::ConfigureTokenPayment {},
+                &[&self.admin],
+            )
+            .unwrap();
+        (mint, treasury_token_account)
+    }
+
+    pub fn add_curator(&mut self) -> Pubkey {
+        let curator = self.bank.wallet(5);
+        self.bank
+            .send(
+                accounts::ManageCurators {
+                    platform_state: self.platform_state,
+                    admin: self.admin,
+                },
+                instruction::AddCurator { curator },
+                &[&self.admin],
+            )
+            .unwrap();
+        curator
+    }
+
+    /// Address the next `add_movie` creates.
+    pub fn next_movie(&self) -> Pubkey {
+        movie_address(self.state().movie_count)
+    }
+
+    pub fn add_movie_accounts(&self, authority: Pubkey) -> accounts::AddMovie {
+        let genre: Genre = self.bank.get(&self.genre);
+        accounts::AddMovie {
+            movie: self.next_movie(),
+            platform_state: self.platform_state,
+            genre: self.genre,
+            genre_page: genre_page_address(GENRE_ID, genre.next_page()),
+            authority,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn add_movie(&mut self, authority: Pubkey, title: &str) -> Result<Pubkey, ProgramError> {
+        let accounts = self.add_movie_accounts(authority);
+        let movie = accounts.movie;
+        self.bank.send(
+            accounts,
+            instruction::AddMovie {
+                title: title.to_string(),
+                description: "A film".to_string(),
+                video_url: "https://cdn.example/film.m3u8".to_string(),
+                genre_id: GENRE_ID,
+            },
+            &[&authority],
+        )?;
+        Ok(movie)
+    }
+
+    pub fn watch_accounts(
+        &self,
+        viewer: Pubkey,
+        user_account: Option<Pubkey>,
+        movie: Pubkey,
+    ) -> accounts::WatchMovie {
+        let state = self.state();
+        let added_by = self.bank.get::<Movie>(&movie).added_by;
+        accounts::WatchMovie {
+            user_account,
+            rental: None,
+            access_pass: None,
+            pass_token_account: None,
+            movie,
+            platform_state: self.platform_state,
+            revenue_epoch: epoch_address(state.current_epoch),
+            creator_stats: creator_stats_address(state.current_epoch, &added_by),
+            watch_record: watch_record_address(&movie, &viewer),
+            viewer,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn watch(
+        &mut self,
+        viewer: Pubkey,
+        user_account: Option<Pubkey>,
+        movie: Pubkey,
+    ) -> Result<(), ProgramError> {
+        let accounts = self.watch_accounts(viewer, user_account, movie);
+        self.bank.send(accounts, instruction::WatchMovie {}, &[&viewer])
+    }
+
+    /// Warps past the end of the current epoch and closes it.
+    pub fn close_epoch(&mut self) -> Result<(), ProgramError> {
+        let state = self.state();
+        self.bank.warp(EPOCH_DURATION);
+        self.bank.send(
+            accounts::CloseEpoch {
+                platform_state: self.platform_state,
+                revenue_epoch: epoch_address(state.current_epoch),
+                next_epoch: epoch_address(state.current_epoch + 1),
+                treasury: self.treasury,
+                payer: self.admin,
+                system_program: system_program::ID,
+            },
+            instruction::CloseEpoch {},
+            &[&self.admin],
+        )
+    }
+}
+
+pub fn movie_address(id: u64) -> Pubkey {
+    pda(&[b"movie", &id.to_le_bytes()])
+}
+
+pub fn genre_address(genre_id: u16) -> Pubkey {
+    pda(&[b"genre", &genre_id.to_le_bytes()])
+}
+
+pub fn genre_name_address(name: &str) -> Pubkey {
+    pda(&[b"genre_name", name.as_bytes()])
+}
+
+pub fn genre_page_address(genre_id: u16, page: u64) -> Pubkey {
+    pda(&[b"genre_page", &genre_id.to_le_bytes(), &page.to_le_bytes()])
+}
+
+pub fn epoch_address(index: u64) -> Pubkey {
+    pda(&[b"epoch", &index.to_le_bytes()])
+}
+
+pub fn creator_stats_address(epoch: u64, creator: &Pubkey) -> Pubkey {
+    pda(&[b"creator_epoch", &epoch.to_le_bytes(), creator.as_ref()])
+}
+
+pub fn watch_record_address(movie: &Pubkey, viewer: &Pubkey) -> Pubkey {
+    pda(&[b"watch", movie.as_ref(), viewer.as_ref()])
+}
+
+pub fn rental_address(movie: &Pubkey, renter: &Pubkey) -> Pubkey {
+    pda(&[b"rental", movie.as_ref(), renter.as_ref()])
+}
+
+pub fn access_pass_address(pass_mint: &Pubkey) -> Pubkey {
+    pda(&[b"access_pass", pass_mint.as_ref()])
+}
+
+pub fn review_address(movie: &Pubkey, owner: &Pubkey) -> Pubkey {
+    pda(&[b"review", movie.as_ref(), owner.as_ref()])
+}
@@ -0,0 +1,260 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{hash::hash, program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, pda, Platform, PLAN_DURATION, PLAN_ID, SOL_PRICE};
+use netmirror::{
+    accounts, instruction, NetMirrorError, RefundPolicy, SubscriptionGifted, UserAccount, Voucher, VoucherRedeemed,
+};
+
+const CODE: &[u8] = b"HAPPY-BIRTHDAY-2026";
+
+fn gift(platform: &mut Platform, gifter: Pubkey, recipient: Pubkey) -> Result<(), ProgramError> {
+    gift_plan(platform, PLAN_ID, gifter, recipient)
+}
+
+fn gift_plan(platform: &mut Platform, plan_id: u16, gifter: Pubkey, recipient: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::GiftSubscription {
+            user_account: pda(&[b"user", recipient.as_ref()]),
+            platform_state: platform.platform_state,
+            plan: pda(&[b"plan", &plan_id.to_le_bytes()]),
+            gifter,
+            treasury: platform.treasury,
+            system_program: system_program::ID,
+        },
+        instruction::GiftSubscription {
+            _plan_id: plan_id,
+            recipient,
+        },
+        &[&gifter],
+    )
+}
+
+fn voucher_address(code: &[u8]) -> Pubkey {
+    pda(&[b"voucher", hash(code).as_ref()])
+}
+
+fn create_voucher(platform: &mut Platform, purchaser: Pubkey, code: &[u8]) -> Pubkey {
+    let voucher = voucher_address(code);
+    platform
+        .bank
+        .send(
+            accounts::CreateVoucher {
+                voucher,
+                platform_state: platform.platform_state,
+                plan: platform.plan,
+                purchaser,
+                treasury: platform.treasury,
+                system_program: system_program::ID,
+            },
+            instruction::CreateVoucher {
+                code_hash: hash(code).to_bytes(),
+                _plan_id: PLAN_ID,
+            },
+            &[&purchaser],
+        )
+        .unwrap();
+    voucher
+}
+
+fn redeem(
+    platform: &mut Platform,
+    voucher: Pubkey,
+    purchaser: Pubkey,
+    owner: Pubkey,
+    code: &[u8],
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::RedeemVoucher {
+            voucher,
+            user_account: pda(&[b"user", owner.as_ref()]),
+            platform_state: platform.platform_state,
+            purchaser,
+            owner,
+        },
+        instruction::RedeemVoucher { code: code.to_vec() },
+        &[&owner],
+    )
+}
+
+#[test]
+fn gift_subscription_credits_the_recipient_and_charges_the_gifter() {
+    let mut platform = Platform::new();
+    let (recipient, user_account) = platform.create_user();
+    let gifter = platform.bank.wallet(1);
+    let gifter_before = platform.bank.lamports(&gifter);
+    let treasury_before = platform.bank.lamports(&platform.treasury);
+
+    gift(&mut platform, gifter, recipient).unwrap();
+
+    assert_eq!(platform.bank.lamports(&gifter), gifter_before - SOL_PRICE);
+    assert_eq!(platform.bank.lamports(&platform.treasury), treasury_before + SOL_PRICE);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+    assert_eq!(user.plan, platform.plan);
+    assert_eq!(user.paid_amount, 0);
+
+    let events = platform.bank.events::<SubscriptionGifted>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].recipient, recipient);
+    assert_eq!(events[0].gifter, gifter);
+    assert_eq!(events[0].amount, SOL_PRICE);
+}
+
+#[test]
+fn gifted_time_stacks_on_a_paid_term_without_becoming_refundable() {
+    let mut platform = Platform::new();
+    let (recipient, user_account) = platform.subscribed_user();
+    let paid_expiry = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let gifter = platform.bank.wallet(1);
+
+    gift(&mut platform, gifter, recipient).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, paid_expiry + PLAN_DURATION);
+    assert_eq!(user.paid_until, paid_expiry);
+    assert_eq!(user.paid_amount, SOL_PRICE);
+
+    // Half the paid term is used, so half the price comes back and the gifted period is kept.
+    platform.bank.warp(PLAN_DURATION / 2);
+    let owner_before = platform.bank.lamports(&recipient);
+    platform.cancel(recipient, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&recipient), owner_before + SOL_PRICE / 2);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+    assert_eq!(user.paid_amount, 0);
+}
+
+#[test]
+fn gifted_time_is_not_refunded_once_the_paid_term_is_used_up() {
+    let mut platform = Platform::new();
+    let (recipient, user_account) = platform.subscribed_user();
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let gifter = platform.bank.wallet(1);
+    gift(&mut platform, gifter, recipient).unwrap();
+
+    platform.bank.warp(PLAN_DURATION);
+    let owner_before = platform.bank.lamports(&recipient);
+    platform.cancel(recipient, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&recipient), owner_before);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+}
+
+#[test]
+fn gifts_never_change_the_recipients_plan_or_household() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+    platform.create_plan(2, 0, 0);
+    let paid_expiry = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+    let griefer = platform.bank.wallet(1);
+
+    gift_plan(&mut platform, 2, griefer, owner).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.plan, platform.plan);
+    assert_eq!(user.members, vec![member]);
+    assert_eq!(user.subscription_expiry, paid_expiry + PLAN_DURATION);
+}
+
+#[test]
+fn gifts_record_the_plan_on_accounts_without_one() {
+    let mut platform = Platform::new();
+    let (recipient, user_account) = platform.create_user();
+    let plan = platform.create_plan(2, 0, 0);
+    let gifter = platform.bank.wallet(1);
+
+    gift_plan(&mut platform, 2, gifter, recipient).unwrap();
+
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).plan, plan);
+}
+
+#[test]
+fn gift_subscription_needs_the_recipients_account() {
+    let mut platform = Platform::new();
+    let (_, user_account) = platform.create_user();
+    let gifter = platform.bank.wallet(1);
+    let recipient = platform.bank.wallet(1);
+
+    let result = platform.bank.send(
+        accounts::GiftSubscription {
+            user_account,
+            platform_state: platform.platform_state,
+            plan: platform.plan,
+            gifter,
+            treasury: platform.treasury,
+            system_program: system_program::ID,
+        },
+        instruction::GiftSubscription {
+            _plan_id: PLAN_ID,
+            recipient,
+        },
+        &[&gifter],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+    assert!(gift(&mut platform, gifter, recipient).is_err());
+}
+
+#[test]
+fn voucher_is_redeemed_once_with_its_code() {
+    let mut platform = Platform::new();
+    let purchaser = platform.bank.wallet(1);
+    let treasury_before = platform.bank.lamports(&platform.treasury);
+    let voucher = create_voucher(&mut platform, purchaser, CODE);
+    assert_eq!(platform.bank.lamports(&platform.treasury), treasury_before + SOL_PRICE);
+
+    let stored: Voucher = platform.bank.get(&voucher);
+    assert_eq!(stored.code_hash, hash(CODE).to_bytes());
+    assert_eq!(stored.duration, PLAN_DURATION);
+    assert_eq!(stored.amount, SOL_PRICE);
+
+    let rent = platform.bank.lamports(&voucher);
+    let purchaser_before = platform.bank.lamports(&purchaser);
+    let (owner, user_account) = platform.create_user();
+    redeem(&mut platform, voucher, purchaser, owner, CODE).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+    assert_eq!(user.paid_amount, 0);
+    assert!(platform.bank.account(&voucher).is_none());
+    assert_eq!(platform.bank.lamports(&purchaser), purchaser_before + rent);
+
+    let events = platform.bank.events::<VoucherRedeemed>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, owner);
+
+    let (other, _) = platform.create_user();
+    assert!(redeem(&mut platform, voucher, purchaser, other, CODE).is_err());
+}
+
+#[test]
+fn redeem_voucher_rejects_the_wrong_code() {
+    let mut platform = Platform::new();
+    let purchaser = platform.bank.wallet(1);
+    let voucher = create_voucher(&mut platform, purchaser, CODE);
+    let (owner, user_account) = platform.create_user();
+
+    let result = redeem(&mut platform, voucher, purchaser, owner, b"GUESS");
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidVoucherCode)));
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).subscription_expiry, 0);
+}
+
+#[test]
+fn redeem_voucher_returns_rent_only_to_the_purchaser() {
+    let mut platform = Platform::new();
+    let purchaser = platform.bank.wallet(1);
+    let voucher = create_voucher(&mut platform, purchaser, CODE);
+    let (owner, _) = platform.create_user();
+
+    let result = redeem(&mut platform, voucher, owner, owner, CODE);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+}
@@ -0,0 +1,354 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, review_address, Platform, PLAN_DURATION};
+use netmirror::{accounts, instruction, Movie, MovieRated, NetMirrorError, Review, ReviewDeleted, ReviewHidden};
+
+fn rate_accounts(platform: &Platform, movie: Pubkey, owner: Pubkey, user_account: Pubkey) -> accounts::RateMovie {
+    accounts::RateMovie {
+        movie,
+        user_account,
+        review: review_address(&movie, &owner),
+        platform_state: platform.platform_state,
+        owner,
+        system_program: system_program::ID,
+    }
+}
+
+fn rate(
+    platform: &mut Platform,
+    movie: Pubkey,
+    owner: Pubkey,
+    user_account: Pubkey,
+    rating: u8,
+) -> Result<(), ProgramError> {
+    let accounts = rate_accounts(platform, movie, owner, user_account);
+    platform.bank.send(
+        accounts,
+        instruction::RateMovie {
+            rating,
+            body: Some("Worth it".to_string()),
+        },
+        &[&owner],
+    )
+}
+
+fn update(
+    platform: &mut Platform,
+    movie: Pubkey,
+    owner: Pubkey,
+    user_account: Pubkey,
+    rating: u8,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::UpdateReview {
+            movie,
+            user_account,
+            review: review_address(&movie, &owner),
+            platform_state: platform.platform_state,
+            owner,
+        },
+        instruction::UpdateReview {
+            rating,
+            body: Some("Changed my mind".to_string()),
+        },
+        &[&owner],
+    )
+}
+
+fn delete(platform: &mut Platform, movie: Pubkey, owner: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::DeleteReview {
+            movie,
+            review: review_address(&movie, &owner),
+            platform_state: platform.platform_state,
+            owner,
+        },
+        instruction::DeleteReview {},
+        &[&owner],
+    )
+}
+
+fn hide(platform: &mut Platform, moderator: Pubkey, movie: Pubkey, reviewer: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::HideReview {
+            movie,
+            review: review_address(&movie, &reviewer),
+            platform_state: platform.platform_state,
+            moderator,
+        },
+        instruction::HideReview {},
+        &[&moderator],
+    )
+}
+
+fn remove(platform: &mut Platform, moderator: Pubkey, movie: Pubkey, reviewer: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::RemoveReview {
+            movie,
+            review: review_address(&movie, &reviewer),
+            platform_state: platform.platform_state,
+            moderator,
+        },
+        instruction::RemoveReview {},
+        &[&moderator],
+    )
+}
+
+fn average_rating(platform: &mut Platform, movie: Pubkey) -> u64 {
+    platform
+        .bank
+        .send(accounts::ViewMovie { movie }, instruction::AverageRating {}, &[])
+        .unwrap();
+    platform.bank.return_data()
+}
+
+#[test]
+fn rate_movie_records_the_review_and_updates_the_average() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (first, first_account) = platform.subscribed_user();
+    let (second, second_account) = platform.subscribed_user();
+
+    rate(&mut platform, movie, first, first_account, 5).unwrap();
+    rate(&mut platform, movie, second, second_account, 4).unwrap();
+
+    let review: Review = platform.bank.get(&review_address(&movie, &first));
+    assert_eq!(review.reviewer, first);
+    assert_eq!(review.movie, movie);
+    assert_eq!(review.rating, 5);
+    assert_eq!(review.body, "Worth it");
+    assert!(!review.is_hidden);
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.rating_sum, 9);
+    assert_eq!(state.rating_count, 2);
+    assert_eq!(average_rating(&mut platform, movie), 450);
+}
+
+#[test]
+fn rate_movie_emits_the_new_aggregates() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    rate(&mut platform, movie, owner, user_account, 3).unwrap();
+
+    let events = platform.bank.events::<MovieRated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].reviewer, owner);
+    assert_eq!(events[0].rating, 3);
+    assert_eq!(events[0].rating_sum, 3);
+    assert_eq!(events[0].rating_count, 1);
+}
+
+#[test]
+fn unrated_movies_average_zero() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+
+    assert_eq!(average_rating(&mut platform, movie), 0);
+}
+
+#[test]
+fn rate_movie_rejects_out_of_range_scores() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    for rating in [Review::MIN_RATING - 1, Review::MAX_RATING + 1] {
+        let result = rate(&mut platform, movie, owner, user_account, rating);
+        assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidRating)));
+    }
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_count, 0);
+}
+
+#[test]
+fn rate_movie_requires_an_active_subscription() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (never, never_account) = platform.create_user();
+    let (lapsed, lapsed_account) = platform.subscribed_user();
+    platform.bank.warp(PLAN_DURATION);
+
+    let result = rate(&mut platform, movie, never, never_account, 4);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+    let result = rate(&mut platform, movie, lapsed, lapsed_account, 4);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::SubscriptionExpired)));
+}
+
+#[test]
+fn rate_movie_allows_one_review_per_viewer() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    rate(&mut platform, movie, owner, user_account, 5).unwrap();
+    assert!(rate(&mut platform, movie, owner, user_account, 1).is_err());
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.rating_sum, 5);
+    assert_eq!(state.rating_count, 1);
+}
+
+#[test]
+fn rate_movie_rejects_someone_elses_user_account() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (_, subscriber_account) = platform.subscribed_user();
+    let freeloader = platform.bank.wallet(1);
+
+    let result = rate(&mut platform, movie, freeloader, subscriber_account, 5);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn rate_movie_rejects_a_review_address_for_another_movie() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let other_movie = platform.add_movie(admin, "Nosferatu").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    let mut accounts = rate_accounts(&platform, movie, owner, user_account);
+    accounts.review = review_address(&other_movie, &owner);
+    let result = platform.bank.send(
+        accounts,
+        instruction::RateMovie {
+            rating: 5,
+            body: None,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn update_review_replaces_the_score_in_the_average() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    rate(&mut platform, movie, owner, user_account, 5).unwrap();
+
+    update(&mut platform, movie, owner, user_account, 2).unwrap();
+
+    let review: Review = platform.bank.get(&review_address(&movie, &owner));
+    assert_eq!(review.rating, 2);
+    assert_eq!(review.body, "Changed my mind");
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.rating_sum, 2);
+    assert_eq!(state.rating_count, 1);
+}
+
+#[test]
+fn delete_review_drops_the_score_and_allows_rating_again() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    rate(&mut platform, movie, owner, user_account, 5).unwrap();
+    let review = review_address(&movie, &owner);
+    let rent = platform.bank.lamports(&review);
+    let owner_before = platform.bank.lamports(&owner);
+
+    delete(&mut platform, movie, owner).unwrap();
+
+    assert!(platform.bank.account(&review).is_none());
+    assert_eq!(platform.bank.lamports(&owner), owner_before + rent);
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_count, 0);
+    assert_eq!(platform.bank.events::<ReviewDeleted>()[0].deleted_by, owner);
+
+    rate(&mut platform, movie, owner, user_account, 3).unwrap();
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_sum, 3);
+}
+
+#[test]
+fn hide_review_stops_the_score_counting() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (first, first_account) = platform.subscribed_user();
+    let (second, second_account) = platform.subscribed_user();
+    rate(&mut platform, movie, first, first_account, 1).unwrap();
+    rate(&mut platform, movie, second, second_account, 5).unwrap();
+
+    hide(&mut platform, admin, movie, first).unwrap();
+
+    let events = platform.bank.events::<ReviewHidden>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].reviewer, first);
+    assert_eq!(events[0].moderator, admin);
+    assert!(platform.bank.get::<Review>(&review_address(&movie, &first)).is_hidden);
+    assert_eq!(average_rating(&mut platform, movie), 500);
+
+    let result = hide(&mut platform, admin, movie, first);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::ReviewHidden)));
+}
+
+#[test]
+fn hidden_reviews_cannot_be_edited_deleted_or_posted_again() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    rate(&mut platform, movie, owner, user_account, 1).unwrap();
+    hide(&mut platform, admin, movie, owner).unwrap();
+
+    let result = update(&mut platform, movie, owner, user_account, 5);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::ReviewHidden)));
+    let result = delete(&mut platform, movie, owner);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::ReviewHidden)));
+    assert!(rate(&mut platform, movie, owner, user_account, 5).is_err());
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.rating_sum, 0);
+    assert_eq!(state.rating_count, 0);
+}
+
+#[test]
+fn remove_review_erases_the_text_and_keeps_the_takedown() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    rate(&mut platform, movie, owner, user_account, 1).unwrap();
+
+    remove(&mut platform, admin, movie, owner).unwrap();
+
+    let review: Review = platform.bank.get(&review_address(&movie, &owner));
+    assert!(review.is_hidden);
+    assert!(review.body.is_empty());
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_count, 0);
+    assert_eq!(platform.bank.events::<ReviewDeleted>()[0].deleted_by, admin);
+
+    assert!(rate(&mut platform, movie, owner, user_account, 5).is_err());
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_count, 0);
+}
+
+#[test]
+fn moderation_is_limited_to_moderators() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    rate(&mut platform, movie, owner, user_account, 1).unwrap();
+    let outsider = platform.bank.wallet(1);
+
+    let result = hide(&mut platform, outsider, movie, owner);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotModerator)));
+    let result = remove(&mut platform, outsider, movie, owner);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotModerator)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).rating_count, 1);
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+use common::{anchor_error, Platform, PLAN_DURATION, PLAN_ID, SOL_PRICE, TOKEN_PRICE};
+use netmirror::{
+    accounts, instruction, NetMirrorError, PaymentCurrency, RefundPolicy, SubscriptionCancelled,
+    TokenRevenueWithdrawn, UserAccount,
+};
+
+const GRACE_PERIOD: i64 = 2 * 24 * 60 * 60;
+
+/// A token subscriber: `(owner, user_account, (mint, owner token account, treasury token account))`.
+fn token_subscriber(platform: &mut Platform) -> (Pubkey, Pubkey, (Pubkey, Pubkey, Pubkey)) {
+    let (mint, treasury_token_account) = match platform.state().payment_mint {
+        mint if mint == Pubkey::default() => platform.configure_token_payment(),
+        mint => (mint, platform.state().treasury_token_account),
+    };
+    let (owner, user_account) = platform.create_user();
+    let owner_token_account = platform.bank.create_token_account(&mint, &owner, TOKEN_PRICE);
+    let token_accounts = (mint, owner_token_account, treasury_token_account);
+    platform
+        .bank
+        .send(
+            platform.subscribe_accounts(owner, user_account, Some(token_accounts)),
+            instruction::Subscribe {
+                _plan_id: PLAN_ID,
+                currency: PaymentCurrency::Token,
+            },
+            &[&owner],
+        )
+        .unwrap();
+    (owner, user_account, token_accounts)
+}
+
+fn cancel_with_tokens(
+    platform: &mut Platform,
+    owner: Pubkey,
+    user_account: Pubkey,
+    token_accounts: (Pubkey, Pubkey, Pubkey),
+) {
+    let accounts = platform.cancel_accounts(owner, user_account, Some(token_accounts));
+    platform
+        .bank
+        .send(accounts, instruction::CancelSubscription {}, &[&owner])
+        .unwrap();
+}
+
+fn set_refund_reserve(platform: &mut Platform, refund_reserve: u64, token_refund_reserve: u64) {
+    let admin = platform.admin;
+    platform
+        .bank
+        .send(
+            accounts::SetRefundReserve {
+                platform_state: platform.platform_state,
+                admin,
+            },
+            instruction::SetRefundReserve {
+                refund_reserve,
+                token_refund_reserve,
+            },
+            &[&admin],
+        )
+        .unwrap();
+}
+
+fn withdraw_token_revenue(
+    platform: &mut Platform,
+    admin: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    let state = platform.state();
+    platform.bank.send(
+        accounts::WithdrawTokenRevenue {
+            platform_state: platform.platform_state,
+            payment_mint: state.payment_mint,
+            treasury_token_account: state.treasury_token_account,
+            destination,
+            treasury: platform.treasury,
+            admin,
+            token_program: anchor_spl::token::ID,
+        },
+        instruction::WithdrawTokenRevenue { amount },
+        &[&admin],
+    )
+}
+
+/// Gives the treasury's free balance a counted view so `close_epoch` sweeps it into revenue.
+fn close_epoch_with_views(platform: &mut Platform) {
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (viewer, viewer_account) = platform.subscribed_user();
+    platform.watch(viewer, Some(viewer_account), movie).unwrap();
+    platform.close_epoch().unwrap();
+}
+
+#[test]
+fn without_a_refund_policy_cancelling_keeps_access_until_expiry() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    let expiry = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+    let owner_before = platform.bank.lamports(&owner);
+
+    platform.cancel(owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&owner), owner_before);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert!(!user.auto_renew);
+    assert_eq!(user.subscription_expiry, expiry);
+    assert_eq!(user.paid_amount, SOL_PRICE);
+    assert_eq!(platform.bank.events::<SubscriptionCancelled>()[0].refund, 0);
+
+    let result = platform.cancel(owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::SubscriptionAlreadyCancelled)));
+}
+
+#[test]
+fn prorated_refunds_return_the_unused_part_of_the_term() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let (owner, user_account) = platform.subscribed_user();
+
+    platform.bank.warp(PLAN_DURATION / 4);
+    let owner_before = platform.bank.lamports(&owner);
+    platform.cancel(owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&owner), owner_before + SOL_PRICE * 3 / 4);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now());
+    assert_eq!(user.paid_amount, 0);
+}
+
+#[test]
+fn full_refunds_are_only_paid_within_the_grace_period() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::FullWithinGrace, GRACE_PERIOD);
+    let (early, early_account) = platform.subscribed_user();
+    let (late, late_account) = platform.subscribed_user();
+
+    platform.bank.warp(GRACE_PERIOD);
+    let early_before = platform.bank.lamports(&early);
+    platform.cancel(early, early_account).unwrap();
+    assert_eq!(platform.bank.lamports(&early), early_before + SOL_PRICE);
+
+    platform.bank.warp(1);
+    let late_before = platform.bank.lamports(&late);
+    let late_expiry = platform.bank.get::<UserAccount>(&late_account).subscription_expiry;
+    platform.cancel(late, late_account).unwrap();
+    assert_eq!(platform.bank.lamports(&late), late_before);
+    assert_eq!(platform.bank.get::<UserAccount>(&late_account).subscription_expiry, late_expiry);
+}
+
+#[test]
+fn token_terms_are_not_refunded_without_a_refund_policy() {
+    let mut platform = Platform::new();
+    let (owner, user_account, token_accounts) = token_subscriber(&mut platform);
+
+    cancel_with_tokens(&mut platform, owner, user_account, token_accounts);
+
+    assert_eq!(platform.bank.token_balance(&token_accounts.1), 0);
+    assert_eq!(platform.bank.token_balance(&token_accounts.2), TOKEN_PRICE);
+    assert!(!platform.bank.get::<UserAccount>(&user_account).auto_renew);
+}
+
+#[test]
+fn token_terms_are_refunded_pro_rata_in_tokens() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let (owner, user_account, token_accounts) = token_subscriber(&mut platform);
+    let lamports_before = platform.bank.lamports(&owner);
+
+    platform.bank.warp(PLAN_DURATION / 2);
+    cancel_with_tokens(&mut platform, owner, user_account, token_accounts);
+
+    assert_eq!(platform.bank.token_balance(&token_accounts.1), TOKEN_PRICE / 2);
+    assert_eq!(platform.bank.token_balance(&token_accounts.2), TOKEN_PRICE / 2);
+    assert_eq!(platform.bank.lamports(&owner), lamports_before);
+
+    let events = platform.bank.events::<SubscriptionCancelled>();
+    assert_eq!(events[0].currency, PaymentCurrency::Token);
+    assert_eq!(events[0].refund, TOKEN_PRICE / 2);
+}
+
+#[test]
+fn token_terms_are_refunded_in_full_within_the_grace_period() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::FullWithinGrace, GRACE_PERIOD);
+    let (owner, user_account, token_accounts) = token_subscriber(&mut platform);
+
+    platform.bank.warp(GRACE_PERIOD / 2);
+    cancel_with_tokens(&mut platform, owner, user_account, token_accounts);
+
+    assert_eq!(platform.bank.token_balance(&token_accounts.1), TOKEN_PRICE);
+    assert_eq!(platform.bank.token_balance(&token_accounts.2), 0);
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).subscription_expiry, platform.bank.now());
+}
+
+#[test]
+fn cancelling_waits_until_the_treasury_can_pay_the_refund() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let (owner, user_account) = platform.subscribed_user();
+    close_epoch_with_views(&mut platform);
+
+    let result = platform.cancel(owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InsufficientTreasuryBalance)));
+    assert!(platform.bank.get::<UserAccount>(&user_account).auto_renew);
+
+    let treasury = platform.treasury;
+    platform.bank.airdrop(&treasury, SOL_PRICE);
+    let owner_before = platform.bank.lamports(&owner);
+    platform.cancel(owner, user_account).unwrap();
+
+    let refund = platform.bank.events::<SubscriptionCancelled>()[0].refund;
+    assert!(refund > 0);
+    assert_eq!(platform.bank.lamports(&owner), owner_before + refund);
+    assert!(!platform.bank.get::<UserAccount>(&user_account).auto_renew);
+}
+
+#[test]
+fn the_refund_reserve_is_left_out_of_epoch_revenue() {
+    let mut platform = Platform::new();
+    platform.set_refund_policy(RefundPolicy::FullWithinGrace, PLAN_DURATION);
+    set_refund_reserve(&mut platform, SOL_PRICE, 0);
+    let (owner, user_account) = platform.subscribed_user();
+    close_epoch_with_views(&mut platform);
+
+    let owner_before = platform.bank.lamports(&owner);
+    platform.cancel(owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&owner), owner_before + SOL_PRICE);
+    assert_eq!(platform.state().reserved_revenue, SOL_PRICE);
+}
+
+#[test]
+fn admin_withdraws_token_revenue_above_the_reserve() {
+    let mut platform = Platform::new();
+    let (_, _, (mint, _, treasury_token_account)) = token_subscriber(&mut platform);
+    token_subscriber(&mut platform);
+    set_refund_reserve(&mut platform, 0, TOKEN_PRICE);
+    let admin = platform.admin;
+    let destination = platform.bank.create_token_account(&mint, &admin, 0);
+
+    let result = withdraw_token_revenue(&mut platform, admin, destination, TOKEN_PRICE + 1);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InsufficientTokenRevenue)));
+
+    withdraw_token_revenue(&mut platform, admin, destination, TOKEN_PRICE).unwrap();
+    assert_eq!(platform.bank.token_balance(&destination), TOKEN_PRICE);
+    assert_eq!(platform.bank.token_balance(&treasury_token_account), TOKEN_PRICE);
+
+    let events = platform.bank.events::<TokenRevenueWithdrawn>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].destination, destination);
+    assert_eq!(events[0].amount, TOKEN_PRICE);
+}
+
+#[test]
+fn withdraw_token_revenue_is_admin_only() {
+    let mut platform = Platform::new();
+    let (_, _, (mint, _, _)) = token_subscriber(&mut platform);
+    let intruder = platform.bank.wallet(1);
+    let destination = platform.bank.create_token_account(&mint, &intruder, 0);
+
+    let result = withdraw_token_revenue(&mut platform, intruder, destination, TOKEN_PRICE);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+}
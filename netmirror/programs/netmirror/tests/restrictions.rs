@@ -0,0 +1,226 @@
+mod common;
+
+use anchor_lang::solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use common::{anchor_error, Platform};
+use netmirror::{accounts, instruction, Movie, NetMirrorError, UserAccount, UserAttested};
+
+const EU: u8 = 3;
+const US: u8 = 7;
+const ADULT: u8 = 18;
+
+fn register_attestor(platform: &mut Platform) -> Pubkey {
+    let attestor = platform.bank.wallet(1);
+    let admin = platform.admin;
+    platform
+        .bank
+        .send(
+            accounts::SetAttestor {
+                platform_state: platform.platform_state,
+                admin,
+            },
+            instruction::SetAttestor { attestor },
+            &[&admin],
+        )
+        .unwrap();
+    attestor
+}
+
+fn attest(
+    platform: &mut Platform,
+    attestor: Pubkey,
+    user_account: Pubkey,
+    region: u8,
+    age_tier: u8,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::AttestUser {
+            user_account,
+            platform_state: platform.platform_state,
+            attestor,
+        },
+        instruction::AttestUser { region, age_tier },
+        &[&attestor],
+    )
+}
+
+fn restrict(
+    platform: &mut Platform,
+    authority: Pubkey,
+    movie: Pubkey,
+    content_rating: u8,
+    allowed_regions: u64,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::SetContentRestrictions {
+            movie,
+            platform_state: platform.platform_state,
+            authority,
+        },
+        instruction::SetContentRestrictions {
+            content_rating,
+            allowed_regions,
+        },
+        &[&authority],
+    )
+}
+
+/// An adult-rated movie licensed only in the EU.
+fn restricted_movie(platform: &mut Platform) -> Pubkey {
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    restrict(platform, admin, movie, ADULT, 1 << EU).unwrap();
+    movie
+}
+
+#[test]
+fn attest_user_records_region_and_age_tier() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let (_, user_account) = platform.create_user();
+
+    attest(&mut platform, attestor, user_account, EU, ADULT).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert!(user.attested);
+    assert_eq!(user.region, EU);
+    assert_eq!(user.age_tier, ADULT);
+
+    let events = platform.bank.events::<UserAttested>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user_account, user_account);
+    assert_eq!(events[0].attestor, attestor);
+}
+
+#[test]
+fn attest_user_rejects_anyone_but_the_attestor() {
+    let mut platform = Platform::new();
+    register_attestor(&mut platform);
+    let (owner, user_account) = platform.create_user();
+
+    let result = attest(&mut platform, owner, user_account, EU, ADULT);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotAttestor)));
+    assert!(!platform.bank.get::<UserAccount>(&user_account).attested);
+}
+
+#[test]
+fn attest_user_rejects_regions_outside_the_bitmask() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let (_, user_account) = platform.create_user();
+
+    let result = attest(&mut platform, attestor, user_account, Movie::REGION_COUNT, ADULT);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidRegion)));
+}
+
+#[test]
+fn attest_user_is_halted_while_the_platform_is_paused() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let (_, user_account) = platform.create_user();
+    platform.pause();
+
+    let result = attest(&mut platform, attestor, user_account, EU, ADULT);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::PlatformPaused)));
+    assert!(!platform.bank.get::<UserAccount>(&user_account).attested);
+}
+
+#[test]
+fn new_movies_are_unrestricted() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.content_rating, 0);
+    assert_eq!(state.allowed_regions, Movie::ALL_REGIONS);
+}
+
+#[test]
+fn restricted_movies_play_for_attested_viewers_that_match() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let movie = restricted_movie(&mut platform);
+    let (owner, user_account) = platform.subscribed_user();
+    attest(&mut platform, attestor, user_account, EU, ADULT).unwrap();
+
+    platform.watch(owner, Some(user_account), movie).unwrap();
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 1);
+}
+
+#[test]
+fn restricted_movies_require_an_attestation() {
+    let mut platform = Platform::new();
+    register_attestor(&mut platform);
+    let movie = restricted_movie(&mut platform);
+    let (owner, user_account) = platform.subscribed_user();
+
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::AttestationRequired)));
+}
+
+#[test]
+fn restricted_movies_reject_unlicensed_regions() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let movie = restricted_movie(&mut platform);
+    let (owner, user_account) = platform.subscribed_user();
+    attest(&mut platform, attestor, user_account, US, ADULT).unwrap();
+
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RegionNotAllowed)));
+}
+
+#[test]
+fn restricted_movies_reject_viewers_below_the_content_rating() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let movie = restricted_movie(&mut platform);
+    let (owner, user_account) = platform.subscribed_user();
+    attest(&mut platform, attestor, user_account, EU, 12).unwrap();
+
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::AgeRestricted)));
+}
+
+#[test]
+fn household_members_cannot_watch_age_rated_movies_on_the_owners_attestation() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let movie = restricted_movie(&mut platform);
+    let (owner, user_account) = platform.subscribed_user();
+    attest(&mut platform, attestor, user_account, EU, ADULT).unwrap();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+
+    let result = platform.watch(member, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MemberAgeUnverified)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 0);
+}
+
+#[test]
+fn household_members_share_the_owners_region() {
+    let mut platform = Platform::new();
+    let attestor = register_attestor(&mut platform);
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    restrict(&mut platform, admin, movie, 0, 1 << EU).unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    attest(&mut platform, attestor, user_account, EU, 0).unwrap();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+
+    platform.watch(member, Some(user_account), movie).unwrap();
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 1);
+}
+
+#[test]
+fn set_content_restrictions_rejects_callers_outside_the_catalog_team() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let outsider = platform.bank.wallet(1);
+
+    let result = restrict(&mut platform, outsider, movie, 0, 0);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::Unauthorized)));
+    assert!(!platform.bank.get::<Movie>(&movie).is_restricted());
+}
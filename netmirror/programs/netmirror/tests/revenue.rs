@@ -0,0 +1,196 @@
+mod common;
+
+use anchor_lang::{
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{anchor_error, creator_stats_address, epoch_address, Platform, SOL_PRICE};
+use netmirror::{
+    accounts, instruction, CreatorEpochStats, CreatorRevenueClaimed, EpochClosed, EpochRevenueReleased,
+    NetMirrorError, RevenueEpoch,
+};
+
+fn claim(platform: &mut Platform, epoch: u64, creator: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::ClaimCreatorRevenue {
+            platform_state: platform.platform_state,
+            revenue_epoch: epoch_address(epoch),
+            creator_stats: creator_stats_address(epoch, &creator),
+            treasury: platform.treasury,
+            creator,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimCreatorRevenue { _epoch_index: epoch },
+        &[&creator],
+    )
+}
+
+fn release(platform: &mut Platform, epoch: u64) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::ReleaseEpochRevenue {
+            platform_state: platform.platform_state,
+            revenue_epoch: epoch_address(epoch),
+        },
+        instruction::ReleaseEpochRevenue { _epoch_index: epoch },
+        &[],
+    )
+}
+
+/// Two creators whose movies got two views and one view in epoch 0, out of three subscriptions'
+/// worth of revenue plus one lamport that the pro-rata split can't divide.
+fn epoch_with_two_creators(platform: &mut Platform) -> (Pubkey, Pubkey) {
+    let first = platform.add_curator();
+    let second = platform.add_curator();
+    let first_movie = platform.add_movie(first, "Metropolis").unwrap();
+    let second_movie = platform.add_movie(second, "Nosferatu").unwrap();
+    for movie in [first_movie, first_movie, second_movie] {
+        let (viewer, user_account) = platform.subscribed_user();
+        platform.watch(viewer, Some(user_account), movie).unwrap();
+    }
+    let treasury = platform.treasury;
+    platform.bank.airdrop(&treasury, 1);
+    (first, second)
+}
+
+#[test]
+fn close_epoch_reserves_the_free_treasury_balance_and_opens_the_next_epoch() {
+    let mut platform = Platform::new();
+    epoch_with_two_creators(&mut platform);
+
+    platform.close_epoch().unwrap();
+
+    let closed: RevenueEpoch = platform.bank.get(&epoch_address(0));
+    assert!(closed.is_closed);
+    assert_eq!(closed.total_views, 3);
+    assert_eq!(closed.revenue, 3 * SOL_PRICE + 1);
+    let state = platform.state();
+    assert_eq!(state.current_epoch, 1);
+    assert_eq!(state.reserved_revenue, 3 * SOL_PRICE + 1);
+
+    let next: RevenueEpoch = platform.bank.get(&epoch_address(1));
+    assert_eq!(next.index, 1);
+    assert_eq!(next.start_time, platform.bank.now());
+    assert!(!next.is_closed);
+
+    let events = platform.bank.events::<EpochClosed>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].revenue, 3 * SOL_PRICE + 1);
+}
+
+#[test]
+fn close_epoch_waits_for_the_epoch_to_end() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+
+    let result = platform.bank.send(
+        accounts::CloseEpoch {
+            platform_state: platform.platform_state,
+            revenue_epoch: epoch_address(0),
+            next_epoch: epoch_address(1),
+            treasury: platform.treasury,
+            payer: admin,
+            system_program: system_program::ID,
+        },
+        instruction::CloseEpoch {},
+        &[&admin],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::EpochStillOpen)));
+}
+
+#[test]
+fn an_epoch_without_views_rolls_its_balance_over() {
+    let mut platform = Platform::new();
+    platform.subscribed_user();
+
+    platform.close_epoch().unwrap();
+    assert_eq!(platform.bank.get::<RevenueEpoch>(&epoch_address(0)).revenue, 0);
+    assert_eq!(platform.state().reserved_revenue, 0);
+
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (viewer, user_account) = platform.subscribed_user();
+    platform.watch(viewer, Some(user_account), movie).unwrap();
+    platform.close_epoch().unwrap();
+    assert_eq!(platform.bank.get::<RevenueEpoch>(&epoch_address(1)).revenue, 2 * SOL_PRICE);
+}
+
+#[test]
+fn creators_claim_their_share_pro_rata_to_views() {
+    let mut platform = Platform::new();
+    let (first, second) = epoch_with_two_creators(&mut platform);
+    platform.close_epoch().unwrap();
+
+    let first_before = platform.bank.lamports(&first);
+    claim(&mut platform, 0, first).unwrap();
+    assert_eq!(platform.bank.lamports(&first), first_before + 2 * SOL_PRICE);
+    let events = platform.bank.events::<CreatorRevenueClaimed>();
+    assert_eq!(events[0].views, 2);
+    assert_eq!(events[0].amount, 2 * SOL_PRICE);
+
+    let second_before = platform.bank.lamports(&second);
+    claim(&mut platform, 0, second).unwrap();
+    assert_eq!(platform.bank.lamports(&second), second_before + SOL_PRICE);
+
+    assert!(platform.bank.get::<CreatorEpochStats>(&creator_stats_address(0, &first)).has_claimed);
+    assert_eq!(platform.bank.get::<RevenueEpoch>(&epoch_address(0)).claimed, 3 * SOL_PRICE);
+    // The lamport the split couldn't divide stays reserved until the epoch is released.
+    assert_eq!(platform.state().reserved_revenue, 1);
+}
+
+#[test]
+fn creators_cannot_claim_twice() {
+    let mut platform = Platform::new();
+    let (first, _) = epoch_with_two_creators(&mut platform);
+    platform.close_epoch().unwrap();
+    claim(&mut platform, 0, first).unwrap();
+
+    let result = claim(&mut platform, 0, first);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RevenueAlreadyClaimed)));
+}
+
+#[test]
+fn claims_wait_for_the_epoch_to_close() {
+    let mut platform = Platform::new();
+    let (first, _) = epoch_with_two_creators(&mut platform);
+
+    let result = claim(&mut platform, 0, first);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::EpochStillOpen)));
+}
+
+#[test]
+fn release_returns_unclaimed_shares_and_dust_after_the_claim_window() {
+    let mut platform = Platform::new();
+    let (first, second) = epoch_with_two_creators(&mut platform);
+    platform.close_epoch().unwrap();
+    claim(&mut platform, 0, first).unwrap();
+
+    let result = release(&mut platform, 0);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::ClaimWindowOpen)));
+
+    platform.bank.warp(RevenueEpoch::CLAIM_WINDOW);
+    release(&mut platform, 0).unwrap();
+
+    assert!(platform.bank.get::<RevenueEpoch>(&epoch_address(0)).released);
+    assert_eq!(platform.state().reserved_revenue, 0);
+    let events = platform.bank.events::<EpochRevenueReleased>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amount, SOL_PRICE + 1);
+
+    let result = claim(&mut platform, 0, second);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::EpochReleased)));
+    let result = release(&mut platform, 0);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::EpochReleased)));
+}
+
+#[test]
+fn release_is_halted_while_the_platform_is_paused() {
+    let mut platform = Platform::new();
+    epoch_with_two_creators(&mut platform);
+    platform.close_epoch().unwrap();
+    platform.bank.warp(RevenueEpoch::CLAIM_WINDOW);
+    platform.pause();
+
+    let result = release(&mut platform, 0);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::PlatformPaused)));
+    assert!(!platform.bank.get::<RevenueEpoch>(&epoch_address(0)).released);
+}
@@ -0,0 +1,464 @@
+mod common;
+
+use anchor_lang::{error::ErrorCode, system_program};
+use anchor_lang::solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use common::{anchor_error, pda, Platform, PLAN_DURATION, PLAN_ID, PLAN_MAX_MEMBERS, SOL_PRICE, TOKEN_PRICE};
+use netmirror::{
+    accounts, instruction, AccountCreated, MemberAdded, MemberRemoved, NetMirrorError, PaymentCurrency,
+    RefundPolicy, Subscribed, TrialStarted, UserAccount,
+};
+
+const TRIAL_DURATION: i64 = 7 * 24 * 60 * 60;
+
+fn offer_trials(platform: &mut Platform, trial_duration: i64) -> Result<(), ProgramError> {
+    let admin = platform.admin;
+    platform.bank.send(
+        accounts::SetTrialDuration {
+            platform_state: platform.platform_state,
+            admin,
+        },
+        instruction::SetTrialDuration { trial_duration },
+        &[&admin],
+    )
+}
+
+fn claim_trial(platform: &mut Platform, owner: Pubkey, user_account: Pubkey) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::ClaimTrial {
+            user_account,
+            platform_state: platform.platform_state,
+            owner,
+        },
+        instruction::ClaimTrial {},
+        &[&owner],
+    )
+}
+
+fn remove_member(
+    platform: &mut Platform,
+    owner: Pubkey,
+    user_account: Pubkey,
+    member: Pubkey,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::ManageMembers {
+            user_account,
+            plan: platform.plan,
+            platform_state: platform.platform_state,
+            owner,
+        },
+        instruction::RemoveMember { member },
+        &[&owner],
+    )
+}
+
+#[test]
+fn create_account_starts_without_a_subscription() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.owner, owner);
+    assert_eq!(user.subscription_expiry, 0);
+    assert!(!user.auto_renew);
+    assert!(user.members.is_empty());
+    assert!(!user.trial_used);
+
+    let events = platform.bank.events::<AccountCreated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user_account, user_account);
+    assert_eq!(events[0].owner, owner);
+}
+
+#[test]
+fn create_account_twice_fails() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+
+    let result = platform.bank.send(
+        accounts::CreateUser {
+            user_account,
+            platform_state: platform.platform_state,
+            owner,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAccount {},
+        &[&owner],
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn create_account_rejects_another_wallets_address() {
+    let mut platform = Platform::new();
+    let owner = platform.bank.wallet(1);
+    let other = platform.bank.wallet(1);
+
+    let result = platform.bank.send(
+        accounts::CreateUser {
+            user_account: pda(&[b"user", other.as_ref()]),
+            platform_state: platform.platform_state,
+            owner,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAccount {},
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn subscribe_pays_treasury_and_starts_period() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+    let treasury_before = platform.bank.lamports(&platform.treasury);
+    let owner_before = platform.bank.lamports(&owner);
+
+    platform.subscribe(owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&platform.treasury), treasury_before + SOL_PRICE);
+    assert_eq!(platform.bank.lamports(&owner), owner_before - SOL_PRICE);
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.plan, platform.plan);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+    assert_eq!(user.period_start, platform.bank.now());
+    assert_eq!(user.paid_amount, SOL_PRICE);
+    assert!(user.auto_renew);
+
+    let events = platform.bank.events::<Subscribed>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].currency, PaymentCurrency::Sol);
+    assert_eq!(events[0].amount, SOL_PRICE);
+    assert_eq!(events[0].expiry, user.subscription_expiry);
+}
+
+#[test]
+fn renewing_early_stacks_on_the_remaining_period() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    let first_expiry = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+
+    platform.bank.warp(PLAN_DURATION / 2);
+    platform.subscribe(owner, user_account).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, first_expiry + PLAN_DURATION);
+    assert_eq!(user.paid_amount, 2 * SOL_PRICE);
+}
+
+#[test]
+fn renewing_after_expiry_starts_a_new_period() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+
+    platform.bank.warp(PLAN_DURATION + 1);
+    platform.subscribe(owner, user_account).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+    assert_eq!(user.period_start, platform.bank.now());
+    assert_eq!(user.paid_amount, SOL_PRICE);
+}
+
+#[test]
+fn subscribe_rejects_inactive_plan() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+    platform
+        .bank
+        .send(
+            accounts::UpdatePlan {
+                plan: platform.plan,
+                platform_state: platform.platform_state,
+                admin: platform.admin,
+            },
+            instruction::UpdatePlan {
+                _plan_id: PLAN_ID,
+                price: SOL_PRICE,
+                token_price: TOKEN_PRICE,
+                duration: PLAN_DURATION,
+                max_streams: 2,
+                max_members: PLAN_MAX_MEMBERS,
+                is_active: false,
+            },
+            &[&platform.admin],
+        )
+        .unwrap();
+
+    let result = platform.subscribe(owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::PlanInactive)));
+}
+
+#[test]
+fn subscribe_rejects_someone_elses_user_account() {
+    let mut platform = Platform::new();
+    let (_, victim_account) = platform.create_user();
+    let attacker = platform.bank.wallet(1);
+
+    let result = platform.subscribe(attacker, victim_account);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+    assert_eq!(platform.bank.get::<UserAccount>(&victim_account).subscription_expiry, 0);
+}
+
+#[test]
+fn subscribe_rejects_payment_to_another_treasury() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+    let impostor = platform.bank.wallet(1);
+    let mut accounts = platform.subscribe_accounts(owner, user_account, None);
+    accounts.treasury = impostor;
+
+    let result = platform.bank.send(
+        accounts,
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Sol,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+}
+
+#[test]
+fn subscribe_fails_when_owner_cannot_pay() {
+    let mut platform = Platform::new();
+    let owner = platform.bank.wallet(0);
+    platform.bank.airdrop(&owner, SOL_PRICE);
+    let user_account = pda(&[b"user", owner.as_ref()]);
+    platform
+        .bank
+        .send(
+            accounts::CreateUser {
+                user_account,
+                platform_state: platform.platform_state,
+                owner,
+                system_program: system_program::ID,
+            },
+            instruction::CreateAccount {},
+            &[&owner],
+        )
+        .unwrap();
+
+    assert!(platform.subscribe(owner, user_account).is_err());
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).subscription_expiry, 0);
+}
+
+#[test]
+fn members_watch_on_the_owners_subscription() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    let member = platform.bank.wallet(1);
+
+    let result = platform.watch(member, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotAccountMember)));
+
+    platform.add_member(owner, user_account, member).unwrap();
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).members, vec![member]);
+    let events = platform.bank.events::<MemberAdded>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user_account, user_account);
+    assert_eq!(events[0].member, member);
+
+    platform.watch(member, Some(user_account), movie).unwrap();
+}
+
+#[test]
+fn add_member_is_capped_by_the_plan() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    for _ in 0..PLAN_MAX_MEMBERS {
+        let member = platform.bank.wallet(1);
+        platform.add_member(owner, user_account, member).unwrap();
+    }
+
+    let member = platform.bank.wallet(1);
+    let result = platform.add_member(owner, user_account, member);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TooManyMembers)));
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).members.len(), PLAN_MAX_MEMBERS as usize);
+}
+
+#[test]
+fn add_member_rejects_the_owner_and_existing_members() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+
+    let result = platform.add_member(owner, user_account, owner);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MemberAlreadyAdded)));
+    let result = platform.add_member(owner, user_account, member);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MemberAlreadyAdded)));
+}
+
+#[test]
+fn add_member_requires_a_subscription() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+    let member = platform.bank.wallet(1);
+
+    let result = platform.bank.send(
+        accounts::ManageMembers {
+            user_account,
+            plan: platform.plan,
+            platform_state: platform.platform_state,
+            owner,
+        },
+        instruction::AddMember { member },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+}
+
+#[test]
+fn members_are_managed_by_the_owner_only() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.subscribed_user();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+    let intruder = platform.bank.wallet(1);
+
+    let result = platform.add_member(intruder, user_account, intruder);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+    let result = remove_member(&mut platform, member, user_account, member);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).members, vec![member]);
+}
+
+#[test]
+fn remove_member_revokes_access() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    let member = platform.bank.wallet(1);
+    platform.add_member(owner, user_account, member).unwrap();
+
+    remove_member(&mut platform, owner, user_account, member).unwrap();
+    assert!(platform.bank.get::<UserAccount>(&user_account).members.is_empty());
+    let events = platform.bank.events::<MemberRemoved>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].member, member);
+
+    let result = platform.watch(member, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotAccountMember)));
+    let result = remove_member(&mut platform, owner, user_account, member);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MemberNotFound)));
+}
+
+#[test]
+fn claim_trial_grants_free_access_once() {
+    let mut platform = Platform::new();
+    offer_trials(&mut platform, TRIAL_DURATION).unwrap();
+    let (owner, user_account) = platform.create_user();
+    let owner_before = platform.bank.lamports(&owner);
+
+    claim_trial(&mut platform, owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&owner), owner_before);
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + TRIAL_DURATION);
+    assert_eq!(user.paid_amount, 0);
+    assert!(!user.auto_renew);
+    assert!(user.trial_used);
+
+    let events = platform.bank.events::<TrialStarted>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, owner);
+    assert_eq!(events[0].expiry, user.subscription_expiry);
+
+    platform.bank.warp(TRIAL_DURATION);
+    let result = claim_trial(&mut platform, owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TrialAlreadyUsed)));
+}
+
+#[test]
+fn claim_trial_fails_while_trials_are_off() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+
+    let result = claim_trial(&mut platform, owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TrialsDisabled)));
+    assert!(!platform.bank.get::<UserAccount>(&user_account).trial_used);
+}
+
+#[test]
+fn claim_trial_is_only_for_accounts_that_never_subscribed() {
+    let mut platform = Platform::new();
+    offer_trials(&mut platform, TRIAL_DURATION).unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    platform.bank.warp(PLAN_DURATION + 1);
+
+    let result = claim_trial(&mut platform, owner, user_account);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TrialUnavailable)));
+}
+
+#[test]
+fn claim_trial_rejects_someone_elses_user_account() {
+    let mut platform = Platform::new();
+    offer_trials(&mut platform, TRIAL_DURATION).unwrap();
+    let (_, victim_account) = platform.create_user();
+    let attacker = platform.bank.wallet(1);
+
+    let result = claim_trial(&mut platform, attacker, victim_account);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn subscribing_during_a_trial_starts_a_paid_term_after_it() {
+    let mut platform = Platform::new();
+    offer_trials(&mut platform, TRIAL_DURATION).unwrap();
+    let (owner, user_account) = platform.create_user();
+    claim_trial(&mut platform, owner, user_account).unwrap();
+    let trial_end = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+
+    platform.subscribe(owner, user_account).unwrap();
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, trial_end + PLAN_DURATION);
+    assert_eq!(user.period_start, trial_end);
+    assert_eq!(user.paid_amount, SOL_PRICE);
+    assert!(user.auto_renew);
+}
+
+#[test]
+fn cancelling_during_a_trial_refunds_the_whole_paid_term() {
+    let mut platform = Platform::new();
+    offer_trials(&mut platform, TRIAL_DURATION).unwrap();
+    platform.set_refund_policy(RefundPolicy::Prorated, 0);
+    let (owner, user_account) = platform.create_user();
+    claim_trial(&mut platform, owner, user_account).unwrap();
+    let trial_end = platform.bank.get::<UserAccount>(&user_account).subscription_expiry;
+    platform.subscribe(owner, user_account).unwrap();
+
+    platform.bank.warp(TRIAL_DURATION / 2);
+    let owner_before = platform.bank.lamports(&owner);
+    platform.cancel(owner, user_account).unwrap();
+
+    assert_eq!(platform.bank.lamports(&owner), owner_before + SOL_PRICE);
+    assert_eq!(platform.bank.get::<UserAccount>(&user_account).subscription_expiry, trial_end);
+}
+
+#[test]
+fn set_trial_duration_is_admin_only() {
+    let mut platform = Platform::new();
+    let outsider = platform.bank.wallet(1);
+
+    let result = platform.bank.send(
+        accounts::SetTrialDuration {
+            platform_state: platform.platform_state,
+            admin: outsider,
+        },
+        instruction::SetTrialDuration {
+            trial_duration: TRIAL_DURATION,
+        },
+        &[&outsider],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+    assert_eq!(
+        offer_trials(&mut platform, -1),
+        Err(anchor_error(NetMirrorError::InvalidTrialDuration))
+    );
+}
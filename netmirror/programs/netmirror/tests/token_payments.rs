@@ -0,0 +1,205 @@
+mod common;
+
+use common::{anchor_error, Platform, PLAN_DURATION, PLAN_ID, SOL_PRICE, TOKEN_PRICE};
+use netmirror::{
+    accounts, instruction, NetMirrorError, PaymentCurrency, PlatformState, Subscribed, UserAccount,
+};
+
+#[test]
+fn configure_token_payment_records_mint_and_treasury_account() {
+    let mut platform = Platform::new();
+    let (mint, treasury_token_account) = platform.configure_token_payment();
+
+    let state: PlatformState = platform.bank.get(&platform.platform_state);
+    assert_eq!(state.payment_mint, mint);
+    assert_eq!(state.treasury_token_account, treasury_token_account);
+}
+
+#[test]
+fn configure_token_payment_rejects_non_admin() {
+    let mut platform = Platform::new();
+    let intruder = platform.bank.wallet(1);
+    let mint = platform.bank.create_mint(&intruder, 6);
+    let token_account = platform.bank.create_token_account(&mint, &intruder, 0);
+
+    let result = platform.bank.send(
+        accounts::ConfigureTokenPayment {
+            platform_state: platform.platform_state,
+            payment_mint: mint,
+            treasury_token_account: token_account,
+            admin: intruder,
+        },
+        instruction::ConfigureTokenPayment {},
+        &[&intruder],
+    );
+    assert_eq!(result, Err(anchor_error(anchor_lang::error::ErrorCode::ConstraintHasOne)));
+}
+
+#[test]
+fn subscribe_with_token_moves_plan_token_price_to_treasury() {
+    let mut platform = Platform::new();
+    let (mint, treasury_token_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let owner_token_account = platform.bank.create_token_account(&mint, &owner, 10 * TOKEN_PRICE);
+    let lamports_before = platform.bank.lamports(&owner);
+
+    platform
+        .bank
+        .send(
+            platform.subscribe_accounts(
+                owner,
+                user_account,
+                Some((mint, owner_token_account, treasury_token_account)),
+            ),
+            instruction::Subscribe {
+                _plan_id: PLAN_ID,
+                currency: PaymentCurrency::Token,
+            },
+            &[&owner],
+        )
+        .unwrap();
+
+    assert_eq!(platform.bank.token_balance(&owner_token_account), 9 * TOKEN_PRICE);
+    assert_eq!(platform.bank.token_balance(&treasury_token_account), TOKEN_PRICE);
+    assert_eq!(platform.bank.lamports(&owner), lamports_before);
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.plan, platform.plan);
+    assert_eq!(user.subscription_expiry, platform.bank.now() + PLAN_DURATION);
+
+    let events = platform.bank.events::<Subscribed>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].currency, PaymentCurrency::Token);
+    assert_eq!(events[0].amount, TOKEN_PRICE);
+}
+
+#[test]
+fn subscribe_with_sol_leaves_token_balances_untouched() {
+    let mut platform = Platform::new();
+    let (mint, treasury_token_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let owner_token_account = platform.bank.create_token_account(&mint, &owner, TOKEN_PRICE);
+    let treasury_before = platform.bank.lamports(&platform.treasury);
+
+    platform
+        .bank
+        .send(
+            platform.subscribe_accounts(owner, user_account, None),
+            instruction::Subscribe {
+                _plan_id: PLAN_ID,
+                currency: PaymentCurrency::Sol,
+            },
+            &[&owner],
+        )
+        .unwrap();
+
+    assert_eq!(platform.bank.lamports(&platform.treasury), treasury_before + SOL_PRICE);
+    assert_eq!(platform.bank.token_balance(&owner_token_account), TOKEN_PRICE);
+    assert_eq!(platform.bank.token_balance(&treasury_token_account), 0);
+}
+
+#[test]
+fn subscribe_with_token_fails_when_not_configured() {
+    let mut platform = Platform::new();
+    let (owner, user_account) = platform.create_user();
+
+    let result = platform.bank.send(
+        platform.subscribe_accounts(owner, user_account, None),
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Token,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::TokenPaymentNotConfigured)));
+}
+
+#[test]
+fn subscribe_with_token_requires_token_accounts() {
+    let mut platform = Platform::new();
+    platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+
+    let result = platform.bank.send(
+        platform.subscribe_accounts(owner, user_account, None),
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Token,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::MissingTokenAccounts)));
+}
+
+#[test]
+fn subscribe_with_token_rejects_other_mints() {
+    let mut platform = Platform::new();
+    let (_, treasury_token_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let other_mint = platform.bank.create_mint(&owner, 6);
+    let owner_token_account = platform.bank.create_token_account(&other_mint, &owner, TOKEN_PRICE);
+
+    let result = platform.bank.send(
+        platform.subscribe_accounts(
+            owner,
+            user_account,
+            Some((other_mint, owner_token_account, treasury_token_account)),
+        ),
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Token,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidPaymentMint)));
+}
+
+#[test]
+fn subscribe_with_token_rejects_someone_elses_token_account() {
+    let mut platform = Platform::new();
+    let (mint, treasury_token_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let victim = platform.bank.wallet(1);
+    let victim_token_account = platform.bank.create_token_account(&mint, &victim, TOKEN_PRICE);
+
+    let result = platform.bank.send(
+        platform.subscribe_accounts(
+            owner,
+            user_account,
+            Some((mint, victim_token_account, treasury_token_account)),
+        ),
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Token,
+        },
+        &[&owner],
+    );
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidPayerTokenAccount)));
+    assert_eq!(platform.bank.token_balance(&victim_token_account), TOKEN_PRICE);
+}
+
+#[test]
+fn subscribe_with_token_fails_on_insufficient_balance() {
+    let mut platform = Platform::new();
+    let (mint, treasury_token_account) = platform.configure_token_payment();
+    let (owner, user_account) = platform.create_user();
+    let owner_token_account = platform.bank.create_token_account(&mint, &owner, TOKEN_PRICE - 1);
+
+    let result = platform.bank.send(
+        platform.subscribe_accounts(
+            owner,
+            user_account,
+            Some((mint, owner_token_account, treasury_token_account)),
+        ),
+        instruction::Subscribe {
+            _plan_id: PLAN_ID,
+            currency: PaymentCurrency::Token,
+        },
+        &[&owner],
+    );
+    assert!(result.is_err());
+
+    let user: UserAccount = platform.bank.get(&user_account);
+    assert_eq!(user.subscription_expiry, 0);
+    assert_eq!(platform.bank.token_balance(&owner_token_account), TOKEN_PRICE - 1);
+}
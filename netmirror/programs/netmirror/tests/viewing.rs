@@ -0,0 +1,502 @@
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    system_program,
+};
+use common::{
+    access_pass_address, anchor_error, creator_stats_address, epoch_address, rental_address,
+    watch_record_address, Platform, EPOCH_DURATION, PLAN_DURATION,
+};
+use netmirror::{
+    accounts, instruction, CreatorEpochStats, Movie, MovieRented, MovieWatched, NetMirrorError, PlatformState,
+    Rental, RevenueEpoch, WatchProgressUpdated, WatchRecord,
+};
+
+const RENTAL_PRICE: u64 = 1_000_000;
+const RENTAL_DURATION: i64 = 48 * 60 * 60;
+
+fn set_rental_terms(platform: &mut Platform, movie: Pubkey, is_premium: bool) {
+    let admin = platform.admin;
+    platform
+        .bank
+        .send(
+            accounts::SetRentalTerms {
+                movie,
+                platform_state: platform.platform_state,
+                authority: admin,
+            },
+            instruction::SetRentalTerms {
+                rental_price: RENTAL_PRICE,
+                rental_duration: RENTAL_DURATION,
+                is_premium,
+            },
+            &[&admin],
+        )
+        .unwrap();
+}
+
+fn rent(platform: &mut Platform, renter: Pubkey, movie: Pubkey) -> Result<(), ProgramError> {
+    let added_by = platform.bank.get::<Movie>(&movie).added_by;
+    platform.bank.send(
+        accounts::RentMovie {
+            movie,
+            rental: rental_address(&movie, &renter),
+            platform_state: platform.platform_state,
+            treasury: platform.treasury,
+            added_by,
+            renter,
+            system_program: system_program::ID,
+        },
+        instruction::RentMovie {},
+        &[&renter],
+    )
+}
+
+fn watch_with_rental(platform: &mut Platform, viewer: Pubkey, movie: Pubkey) -> Result<(), ProgramError> {
+    let mut accounts = platform.watch_accounts(viewer, None, movie);
+    accounts.rental = Some(rental_address(&movie, &viewer));
+    platform.bank.send(accounts, instruction::WatchMovie {}, &[&viewer])
+}
+
+/// Registers a fresh mint as an access pass for `movie`, or for the whole catalog when `None`.
+fn register_access_pass(platform: &mut Platform, movie: Option<Pubkey>) -> Pubkey {
+    let admin = platform.admin;
+    let pass_mint = platform.bank.create_mint(&admin, 0);
+    platform
+        .bank
+        .send(
+            accounts::RegisterAccessPass {
+                access_pass: access_pass_address(&pass_mint),
+                pass_mint,
+                platform_state: platform.platform_state,
+                admin,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterAccessPass { movie },
+            &[&admin],
+        )
+        .unwrap();
+    pass_mint
+}
+
+fn watch_with_pass(
+    platform: &mut Platform,
+    viewer: Pubkey,
+    movie: Pubkey,
+    pass_mint: Pubkey,
+    pass_token_account: Pubkey,
+) -> Result<(), ProgramError> {
+    let mut accounts = platform.watch_accounts(viewer, None, movie);
+    accounts.access_pass = Some(access_pass_address(&pass_mint));
+    accounts.pass_token_account = Some(pass_token_account);
+    platform.bank.send(accounts, instruction::WatchMovie {}, &[&viewer])
+}
+
+fn update_progress(
+    platform: &mut Platform,
+    viewer: Pubkey,
+    user_account: Option<Pubkey>,
+    rental: Option<Pubkey>,
+    movie: Pubkey,
+    position_secs: u32,
+) -> Result<(), ProgramError> {
+    platform.bank.send(
+        accounts::UpdateProgress {
+            user_account,
+            rental,
+            access_pass: None,
+            pass_token_account: None,
+            movie,
+            watch_record: watch_record_address(&movie, &viewer),
+            platform_state: platform.platform_state,
+            viewer,
+        },
+        instruction::UpdateProgress { position_secs },
+        &[&viewer],
+    )
+}
+
+#[test]
+fn subscriber_watch_counts_a_view_for_the_movie_and_its_creator() {
+    let mut platform = Platform::new();
+    let curator = platform.add_curator();
+    let movie = platform.add_movie(curator, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    platform.watch(owner, Some(user_account), movie).unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.total_views, 1);
+    assert_eq!(state.unique_viewers, 1);
+
+    let record: WatchRecord = platform.bank.get(&watch_record_address(&movie, &owner));
+    assert_eq!(record.viewer, owner);
+    assert_eq!(record.movie, movie);
+    assert_eq!(record.view_count, 1);
+    assert_eq!(record.first_watched_at, platform.bank.now());
+
+    let stats: CreatorEpochStats = platform.bank.get(&creator_stats_address(0, &curator));
+    assert_eq!(stats.creator, curator);
+    assert_eq!(stats.views, 1);
+    assert_eq!(platform.bank.get::<RevenueEpoch>(&epoch_address(0)).total_views, 1);
+
+    let events = platform.bank.events::<MovieWatched>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].viewer, owner);
+    assert!(events[0].counted);
+}
+
+#[test]
+fn repeat_views_only_count_once_the_cooldown_has_passed() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    platform.watch(owner, Some(user_account), movie).unwrap();
+    platform.bank.warp(60);
+    platform.watch(owner, Some(user_account), movie).unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.total_views, 1);
+    let record: WatchRecord = platform.bank.get(&watch_record_address(&movie, &owner));
+    assert_eq!(record.view_count, 2);
+    assert!(!platform.bank.events::<MovieWatched>()[0].counted);
+
+    platform.bank.warp(PlatformState::DEFAULT_VIEW_COOLDOWN);
+    platform.watch(owner, Some(user_account), movie).unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.total_views, 2);
+    assert_eq!(state.unique_viewers, 1);
+    assert!(platform.bank.events::<MovieWatched>()[0].counted);
+}
+
+#[test]
+fn a_repeat_view_that_opens_an_epoch_still_fills_in_the_creator_stats() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    platform
+        .bank
+        .send(
+            accounts::SetViewCooldown {
+                platform_state: platform.platform_state,
+                admin,
+            },
+            instruction::SetViewCooldown {
+                view_cooldown: 2 * EPOCH_DURATION,
+            },
+            &[&admin],
+        )
+        .unwrap();
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    platform.watch(owner, Some(user_account), movie).unwrap();
+    platform.close_epoch().unwrap();
+
+    platform.watch(owner, Some(user_account), movie).unwrap();
+    assert!(!platform.bank.events::<MovieWatched>()[0].counted);
+
+    let stats: CreatorEpochStats = platform.bank.get(&creator_stats_address(1, &admin));
+    assert_eq!(stats.creator, admin);
+    assert_eq!(stats.epoch, 1);
+    assert_eq!(stats.views, 0);
+    assert_ne!(stats.bump, 0);
+}
+
+#[test]
+fn distinct_viewers_are_counted_separately() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (first, first_account) = platform.subscribed_user();
+    let (second, second_account) = platform.subscribed_user();
+
+    platform.watch(first, Some(first_account), movie).unwrap();
+    platform.watch(second, Some(second_account), movie).unwrap();
+
+    let state: Movie = platform.bank.get(&movie);
+    assert_eq!(state.total_views, 2);
+    assert_eq!(state.unique_viewers, 2);
+}
+
+#[test]
+fn watch_requires_a_subscription() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.create_user();
+
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+
+    let result = platform.watch(owner, None, movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 0);
+}
+
+#[test]
+fn watch_fails_once_the_subscription_expires() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    platform.bank.warp(PLAN_DURATION);
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::SubscriptionExpired)));
+}
+
+#[test]
+fn watch_rejects_borrowing_someone_elses_subscription() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (_, subscriber_account) = platform.subscribed_user();
+    let freeloader = platform.bank.wallet(1);
+
+    let result = platform.watch(freeloader, Some(subscriber_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::NotAccountMember)));
+}
+
+#[test]
+fn watch_rejects_a_watch_record_for_another_viewer() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    let other = platform.bank.wallet(1);
+
+    let mut accounts = platform.watch_accounts(owner, Some(user_account), movie);
+    accounts.watch_record = watch_record_address(&movie, &other);
+    let result = platform.bank.send(accounts, instruction::WatchMovie {}, &[&owner]);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn watch_rejects_an_epoch_other_than_the_current_one() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+
+    let mut accounts = platform.watch_accounts(owner, Some(user_account), movie);
+    accounts.creator_stats = creator_stats_address(1, &admin);
+    let result = platform.bank.send(accounts, instruction::WatchMovie {}, &[&owner]);
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn rent_movie_splits_the_price_between_treasury_and_uploader() {
+    let mut platform = Platform::new();
+    let curator = platform.add_curator();
+    let movie = platform.add_movie(curator, "Metropolis").unwrap();
+    set_rental_terms(&mut platform, movie, true);
+    let renter = platform.bank.wallet(1);
+    let treasury_before = platform.bank.lamports(&platform.treasury);
+    let curator_before = platform.bank.lamports(&curator);
+
+    rent(&mut platform, renter, movie).unwrap();
+
+    let platform_fee =
+        RENTAL_PRICE * PlatformState::DEFAULT_RENTAL_FEE_BPS as u64 / PlatformState::MAX_BPS as u64;
+    assert_eq!(platform.bank.lamports(&platform.treasury), treasury_before + platform_fee);
+    assert_eq!(platform.bank.lamports(&curator), curator_before + RENTAL_PRICE - platform_fee);
+
+    let rental: Rental = platform.bank.get(&rental_address(&movie, &renter));
+    assert_eq!(rental.renter, renter);
+    assert_eq!(rental.expires_at, platform.bank.now() + RENTAL_DURATION);
+
+    let events = platform.bank.events::<MovieRented>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].price, RENTAL_PRICE);
+    assert_eq!(events[0].platform_fee, platform_fee);
+}
+
+#[test]
+fn renting_again_extends_the_window() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    set_rental_terms(&mut platform, movie, true);
+    let renter = platform.bank.wallet(1);
+    rent(&mut platform, renter, movie).unwrap();
+    let first_expiry = platform.bank.get::<Rental>(&rental_address(&movie, &renter)).expires_at;
+
+    platform.bank.warp(RENTAL_DURATION / 2);
+    rent(&mut platform, renter, movie).unwrap();
+
+    let rental: Rental = platform.bank.get(&rental_address(&movie, &renter));
+    assert_eq!(rental.expires_at, first_expiry + RENTAL_DURATION);
+}
+
+#[test]
+fn rent_movie_requires_rental_terms() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let renter = platform.bank.wallet(1);
+
+    let result = rent(&mut platform, renter, movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RentalUnavailable)));
+}
+
+#[test]
+fn premium_movies_need_a_rental_even_for_subscribers() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    set_rental_terms(&mut platform, movie, true);
+    let (owner, user_account) = platform.subscribed_user();
+
+    let result = platform.watch(owner, Some(user_account), movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RentalRequired)));
+
+    rent(&mut platform, owner, movie).unwrap();
+    watch_with_rental(&mut platform, owner, movie).unwrap();
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 1);
+
+    platform.bank.warp(RENTAL_DURATION);
+    let result = watch_with_rental(&mut platform, owner, movie);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RentalRequired)));
+}
+
+#[test]
+fn update_progress_saves_the_resume_position() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    platform.watch(owner, Some(user_account), movie).unwrap();
+
+    update_progress(&mut platform, owner, Some(user_account), None, movie, 1_234).unwrap();
+
+    let record: WatchRecord = platform.bank.get(&watch_record_address(&movie, &owner));
+    assert_eq!(record.resume_position_secs, 1_234);
+    let events = platform.bank.events::<WatchProgressUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].position_secs, 1_234);
+}
+
+#[test]
+fn renters_without_a_subscription_can_save_their_position() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    set_rental_terms(&mut platform, movie, true);
+    let renter = platform.bank.wallet(1);
+    rent(&mut platform, renter, movie).unwrap();
+    watch_with_rental(&mut platform, renter, movie).unwrap();
+    let rental = Some(rental_address(&movie, &renter));
+
+    update_progress(&mut platform, renter, None, rental, movie, 600).unwrap();
+    let record: WatchRecord = platform.bank.get(&watch_record_address(&movie, &renter));
+    assert_eq!(record.resume_position_secs, 600);
+
+    platform.bank.warp(RENTAL_DURATION);
+    let result = update_progress(&mut platform, renter, None, rental, movie, 900);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RentalRequired)));
+}
+
+#[test]
+fn update_progress_requires_access_to_the_movie() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let (owner, user_account) = platform.subscribed_user();
+    platform.watch(owner, Some(user_account), movie).unwrap();
+
+    platform.bank.warp(PLAN_DURATION);
+    let result = update_progress(&mut platform, owner, Some(user_account), None, movie, 60);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::SubscriptionExpired)));
+    let result = update_progress(&mut platform, owner, None, None, movie, 60);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+}
+
+#[test]
+fn movie_passes_open_their_movie_even_when_premium() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let other_movie = platform.add_movie(admin, "Nosferatu").unwrap();
+    set_rental_terms(&mut platform, movie, true);
+    let pass_mint = register_access_pass(&mut platform, Some(movie));
+    let holder = platform.bank.wallet(1);
+    let pass = platform.bank.create_token_account(&pass_mint, &holder, 1);
+
+    watch_with_pass(&mut platform, holder, movie, pass_mint, pass).unwrap();
+    assert_eq!(platform.bank.get::<Movie>(&movie).total_views, 1);
+
+    let result = watch_with_pass(&mut platform, holder, other_movie, pass_mint, pass);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+}
+
+#[test]
+fn catalog_passes_open_the_catalog_but_not_premium_movies() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let premium_movie = platform.add_movie(admin, "Nosferatu").unwrap();
+    set_rental_terms(&mut platform, premium_movie, true);
+    let pass_mint = register_access_pass(&mut platform, None);
+    let holder = platform.bank.wallet(1);
+    let pass = platform.bank.create_token_account(&pass_mint, &holder, 1);
+
+    watch_with_pass(&mut platform, holder, movie, pass_mint, pass).unwrap();
+
+    let result = watch_with_pass(&mut platform, holder, premium_movie, pass_mint, pass);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::RentalRequired)));
+}
+
+#[test]
+fn an_empty_pass_token_account_opens_nothing() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let pass_mint = register_access_pass(&mut platform, None);
+    let holder = platform.bank.wallet(1);
+    let pass = platform.bank.create_token_account(&pass_mint, &holder, 0);
+
+    let result = watch_with_pass(&mut platform, holder, movie, pass_mint, pass);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::UserNotSubscribed)));
+}
+
+#[test]
+fn passes_must_be_held_by_the_viewer_and_match_the_mint() {
+    let mut platform = Platform::new();
+    let admin = platform.admin;
+    let movie = platform.add_movie(admin, "Metropolis").unwrap();
+    let pass_mint = register_access_pass(&mut platform, None);
+    let holder = platform.bank.wallet(1);
+    let borrower = platform.bank.wallet(1);
+    let pass = platform.bank.create_token_account(&pass_mint, &holder, 1);
+    let other_mint = platform.bank.create_mint(&admin, 0);
+    let other_token = platform.bank.create_token_account(&other_mint, &borrower, 1);
+
+    let result = watch_with_pass(&mut platform, borrower, movie, pass_mint, pass);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidAccessPass)));
+    let result = watch_with_pass(&mut platform, borrower, movie, pass_mint, other_token);
+    assert_eq!(result, Err(anchor_error(NetMirrorError::InvalidAccessPass)));
+}
+
+#[test]
+fn register_access_pass_is_admin_only() {
+    let mut platform = Platform::new();
+    let intruder = platform.bank.wallet(1);
+    let pass_mint = platform.bank.create_mint(&intruder, 0);
+
+    let result = platform.bank.send(
+        accounts::RegisterAccessPass {
+            access_pass: access_pass_address(&pass_mint),
+            pass_mint,
+            platform_state: platform.platform_state,
+            admin: intruder,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterAccessPass { movie: None },
+        &[&intruder],
+    );
+    assert_eq!(result, Err(anchor_error(ErrorCode::ConstraintHasOne)));
+}